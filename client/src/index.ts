@@ -16,6 +16,33 @@ import {
   TransactionFromAuthorizationParams,
   DeployFromAuthorizationParams,
   UpdateParams,
+  DeployV2Params,
+  ExecuteV2Params,
+  TransferV2Params,
+  JoinV2Params,
+  SplitV2Params,
+  AuthorizeExecuteParams,
+  AuthorizeFeeParams,
+  AuthorizeDeployFeeParams,
+  DeployAuthorization,
+  BroadcastParams,
+  ExecuteAndBroadcastParams,
+  TransactionStatusParams,
+  TransactionRecord,
+  DeploymentCostV2Params,
+  ExecutionCostV3Params,
+  CostBreakdown,
+  VerifyTransactionParams,
+  VerificationReport,
+  ExecuteBundleParams,
+  BundleResult,
+  ScanRecordsParams,
+  ScanResult,
+  Account,
+  DeriveAccountParams,
+  SignParams,
+  VerifyParams,
+  StatsResult,
 } from './types';
 import { bytesToHex, hexToBytes } from '@noble/curves/abstract/utils';
 
@@ -133,6 +160,7 @@ export class Client {
     }
   }
 
+  /** @deprecated use deployv2 */
   async deploy(params: DeployParams) {
     const func = "deploy"
     if (this.features.find((v) => v == func) == undefined) {
@@ -147,6 +175,7 @@ export class Client {
     return resp.json();
   }
 
+  /** @deprecated use executev2 */
   async execute(params: ExecuteParams) {
     const func = "execute"
     if (this.features.find((v) => v == func) == undefined) {
@@ -161,6 +190,7 @@ export class Client {
     return resp.json();
   }
 
+  /** @deprecated use transferv2 */
   async transfer(params: TransferParams) {
     const func = "transfer"
     if (this.features.find((v) => v == func) == undefined) {
@@ -175,6 +205,7 @@ export class Client {
     return resp.json();
   }
 
+  /** @deprecated use joinv2 */
   async join(params: JoinParams) {
     const func = "join"
     if (this.features.find((v) => v == func) == undefined) {
@@ -189,6 +220,7 @@ export class Client {
     return resp.json();
   }
 
+  /** @deprecated use splitv2 */
   async split(params: SplitParams) {
     const func = "split"
     if (this.features.find((v) => v == func) == undefined) {
@@ -203,6 +235,76 @@ export class Client {
     return resp.json();
   }
 
+  async deployv2(params: DeployV2Params) {
+    const func = "deployv2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async executev2(params: ExecuteV2Params) {
+    const func = "executev2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async transferv2(params: TransferV2Params) {
+    const func = "transferv2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async joinv2(params: JoinV2Params) {
+    const func = "joinv2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async splitv2(params: SplitV2Params) {
+    const func = "splitv2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
   async deployment_cost(params: DeploymentCostParams) {
     const func = "deployment_cost"
     if (this.features.find((v) => v == func) == undefined) {
@@ -230,6 +332,34 @@ export class Client {
     });
     return resp.json();
   }
+  async deployment_costv2(params: DeploymentCostV2Params): Promise<JsonRpcResult<CostBreakdown>> {
+    const func = "deployment_costv2"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async execution_costv3(params: ExecutionCostV3Params): Promise<JsonRpcResult<CostBreakdown>> {
+    const func = "execution_costv3"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
 
   async decrypt_records(params: DecryptRecordsParams) {
     const func = "decrypt_recordsv2"
@@ -273,6 +403,188 @@ export class Client {
     return resp.json();
   }
 
+  async authorize_execute(params: AuthorizeExecuteParams): Promise<JsonRpcResult<string>> {
+    const func = "authorize_execute"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async authorize_fee(params: AuthorizeFeeParams): Promise<JsonRpcResult<string>> {
+    const func = "authorize_fee"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async authorize_deploy_fee(params: AuthorizeDeployFeeParams): Promise<JsonRpcResult<DeployAuthorization>> {
+    const func = "authorize_deploy_fee"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async broadcast(params: BroadcastParams): Promise<JsonRpcResult<TransactionRecord>> {
+    const func = "broadcast"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async execute_and_broadcast(params: ExecuteAndBroadcastParams): Promise<JsonRpcResult<TransactionRecord>> {
+    const func = "execute_and_broadcast"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async transaction_status(params: TransactionStatusParams): Promise<JsonRpcResult<TransactionRecord>> {
+    const func = "transaction_status"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async verify_transaction(params: VerifyTransactionParams): Promise<JsonRpcResult<VerificationReport>> {
+    const func = "verify_transaction"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async execute_bundle(params: ExecuteBundleParams): Promise<JsonRpcResult<BundleResult>> {
+    const func = "execute_bundle"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async scan_records(params: ScanRecordsParams): Promise<JsonRpcResult<ScanResult>> {
+    const func = "scan_records"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async generate_account(): Promise<JsonRpcResult<Account>> {
+    const func = "generate_account"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: [],
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async derive_account(params: DeriveAccountParams): Promise<JsonRpcResult<Account>> {
+    const func = "derive_account"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async sign(params: SignParams): Promise<JsonRpcResult<string>> {
+    const func = "sign"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
+  async verify(params: VerifyParams): Promise<JsonRpcResult<boolean>> {
+    const func = "verify"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: Object.values(params),
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
   async update(params: UpdateParams) {
     const func = "update"
     if (this.features.find((v) => v == func) == undefined) {
@@ -287,6 +599,20 @@ export class Client {
     return resp.json();
   }
 
+  async stats(): Promise<JsonRpcResult<StatsResult>> {
+    const func = "stats"
+    if (this.features.find((v) => v == func) == undefined) {
+      throw new NotImplementedError(func)
+    }
+    let resp = await this.fetch({
+      method: func,
+      params: [],
+      jsonrpc: '2.0',
+      id: 1,
+    });
+    return resp.json();
+  }
+
   async fetch(body: JsonRpcRequest<any>): Promise<Response> {
     let body_json = JSON.stringify(body);
 
@@ -1,3 +1,80 @@
+/** ledger state to prove from without network access, `query` is ignored when set */
+export interface StateSnapshot {
+  state_root: string;
+  /** state path of each input record, by record commitment */
+  state_paths?: { [commitment: string]: string };
+  /** source of each program called or imported, by program id */
+  programs?: { [programId: string]: string };
+  height?: number;
+}
+
+/**
+ * The base fee is computed from the transaction, only the priority fee on top
+ * of it is chosen. A private fee is paid from `fee_record`, a public one from
+ * the account's balance.
+ */
+export interface Fee {
+  priority_fee_in_microcredits?: number;
+  payment?: 'public' | 'private';
+  fee_record?: string;
+}
+
+export interface DeployV2Params {
+  private_key: string;
+  program: string;
+  imports?: { [key: string]: string };
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+export interface ExecuteV2Params {
+  private_key: string;
+  program_id: string;
+  function: string;
+  inputs: string[];
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+export type TransferFunction =
+  | 'public'
+  | 'private'
+  | 'public_to_private'
+  | 'private_to_public';
+
+export interface TransferV2Params {
+  private_key: string;
+  recipient: string;
+  amount: number;
+  function: TransferFunction;
+  input_record?: string;
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+export interface JoinV2Params {
+  private_key: string;
+  first_record: string;
+  second_record: string;
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+export interface SplitV2Params {
+  private_key: string;
+  record: string;
+  amount: number;
+  /** split has no fee, anything but the default is refused */
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+/** @deprecated use DeployV2Params */
 export interface DeployParams {
   private_key: string;
   program: string;
@@ -5,8 +82,10 @@ export interface DeployParams {
   imports?: { [key: string]: string };
   priority_fee_in_microcredits?: number;
   query?: string;
+  snapshot?: StateSnapshot;
 }
 
+/** @deprecated use ExecuteV2Params */
 export interface ExecuteParams {
   private_key: string;
   program_id: string;
@@ -16,8 +95,10 @@ export interface ExecuteParams {
   imports?: { [key: string]: string };
   fee?: number;
   query?: string;
+  snapshot?: StateSnapshot;
 }
 
+/** @deprecated use TransferV2Params */
 export interface TransferParams {
   private_key: string;
   recipient: string;
@@ -27,8 +108,10 @@ export interface TransferParams {
   fee_record?: string;
   fee?: number;
   query?: string;
+  snapshot?: StateSnapshot;
 }
 
+/** @deprecated use JoinV2Params */
 export interface JoinParams {
   private_key: string;
   first_record: string;
@@ -36,13 +119,16 @@ export interface JoinParams {
   fee_record?: string;
   fee?: number;
   query?: string;
+  snapshot?: StateSnapshot;
 }
 
+/** @deprecated use SplitV2Params */
 export interface SplitParams {
   private_key: string;
   record: string;
   amount: number;
   query?: string;
+  snapshot?: StateSnapshot;
 }
 
 export interface DeploymentCostParams {
@@ -55,6 +141,38 @@ export interface ExecutionCostParams {
   function: string,
   inputs: string[],
   query?: string,
+  snapshot?: StateSnapshot,
+}
+
+export interface DeploymentCostV2Params {
+  program: string;
+  imports?: { [key: string]: string };
+  fee?: Fee;
+}
+
+export interface ExecutionCostV3Params {
+  program_id: string;
+  function: string;
+  inputs: string[];
+  fee?: Fee;
+  query?: string;
+  snapshot?: StateSnapshot;
+}
+
+export interface FinalizeCost {
+  program_id: string;
+  function: string;
+  cost_in_microcredits: number;
+}
+
+export interface CostBreakdown {
+  total_in_microcredits: number;
+  base_fee_in_microcredits: number;
+  storage_in_microcredits: number;
+  namespace_in_microcredits: number;
+  synthesis_in_microcredits: number;
+  finalize: FinalizeCost[];
+  priority_fee_in_microcredits: number;
 }
 
 export interface DecryptRecordsParams {
@@ -67,6 +185,7 @@ export interface TransactionFromAuthorizationParams {
   execute_authorization_str: string,
   fee_authorization_str: string,
   query?: string,
+  snapshot?: StateSnapshot,
 }
 
 export interface DeployFromAuthorizationParams {
@@ -75,6 +194,189 @@ export interface DeployFromAuthorizationParams {
   owner_str: string,
   fee_authorization_str: string,
   query?: string,
+  snapshot?: StateSnapshot,
+}
+
+export interface AuthorizeExecuteParams {
+  private_key: string;
+  program_id: string;
+  function: string;
+  inputs: string[];
+  query?: string;
+}
+
+export interface AuthorizeFeeParams {
+  private_key: string;
+  execute_authorization_str: string;
+  /** estimated by running the authorized call when left out */
+  base_fee_in_microcredits?: number;
+  fee?: Fee;
+  query?: string;
+}
+
+export interface AuthorizeDeployFeeParams {
+  private_key: string;
+  program: string;
+  imports?: { [key: string]: string };
+  /** the deployment cost when left out */
+  base_fee_in_microcredits?: number;
+  fee?: Fee;
+  query?: string;
+}
+
+export interface DeployAuthorization {
+  owner: string;
+  fee_authorization: string;
+}
+
+export interface BroadcastParams {
+  transaction: string;
+  query?: string;
+}
+
+export interface ExecuteAndBroadcastParams {
+  private_key: string;
+  program_id: string;
+  function: string;
+  inputs: string[];
+  fee?: Fee;
+  query?: string;
+}
+
+export interface TransactionStatusParams {
+  transaction_id: string;
+  query?: string;
+}
+
+export interface TransactionRecord {
+  id: string;
+  status: 'pending' | 'accepted' | 'rejected';
+  endpoints: string[];
+  broadcast_at?: string;
+  updated_at: string;
+  error?: string;
+}
+
+/** `$<step>.<index>` stands for a record an earlier step returned, in any record or `execute` input */
+export type BundleStep =
+  | { method: 'execute'; program_id: string; function: string; inputs: string[]; fee?: Fee }
+  | {
+      method: 'transfer';
+      recipient: string;
+      amount: number;
+      function: TransferFunction;
+      input_record?: string;
+      fee?: Fee;
+    }
+  | { method: 'join'; first_record: string; second_record: string; fee?: Fee }
+  | { method: 'split'; record: string; amount: number };
+
+export interface ExecuteBundleParams {
+  private_key: string;
+  steps: BundleStep[];
+  /** broadcast the transactions, needed when steps spend records of earlier ones */
+  broadcast?: boolean;
+  query?: string;
+}
+
+export interface BundleTransaction {
+  transaction: string;
+  /** records owned by the caller, in output order */
+  records: string[];
+  broadcast?: TransactionRecord;
+}
+
+export interface BundleResult {
+  transactions: BundleTransaction[];
+  /** steps not run because a transaction they spend records of was still pending, to send again once it is accepted. references to `transactions` are replaced with records */
+  remaining: BundleStep[];
+}
+
+export interface VerifyTransactionParams {
+  transaction: string;
+  query?: string;
+}
+
+export interface Check {
+  valid: boolean;
+  error?: string;
+}
+
+export interface TransitionReport extends Check {
+  id: string;
+  program_id: string;
+  function: string;
+}
+
+export interface FeeReport extends Check {
+  transition_id: string;
+  payment: 'public' | 'private';
+  base_fee_in_microcredits?: number;
+  priority_fee_in_microcredits?: number;
+}
+
+export interface VerificationReport {
+  id: string;
+  kind: 'deploy' | 'execute';
+  valid: boolean;
+  transitions: TransitionReport[];
+  proof: Check;
+  owner?: Check;
+  fee?: FeeReport;
+}
+
+export interface ScanRecordsParams {
+  view_key: string;
+  start_height: number;
+  /** exclusive */
+  end_height: number;
+  query?: string;
+}
+
+export interface ScanJob {
+  id: string;
+  start_height: number;
+  end_height: number;
+  next_height: number;
+  endpoints: string[];
+  records_found: number;
+  started_at: string;
+  updated_at: string;
+  done: boolean;
+  error?: string;
+}
+
+export interface ScannedRecord {
+  commitment: string;
+  height: number;
+  record: string;
+}
+
+export interface ScanResult {
+  job: ScanJob;
+  records: ScannedRecord[];
+  running: boolean;
+}
+
+export interface Account {
+  private_key: string;
+  view_key: string;
+  address: string;
+}
+
+export interface DeriveAccountParams {
+  private_key: string;
+}
+
+export interface SignParams {
+  private_key: string;
+  message: string;
+}
+
+export interface VerifyParams {
+  address: string;
+  message: string;
+  signature: string;
 }
 
 export interface UpdateParams {
@@ -95,8 +397,38 @@ export interface JsonRpcRequest<T> {
   id: 1;
 }
 
+export interface ActiveNetwork {
+  name: string;
+  kind: 'mainnet' | 'testnet' | 'devnet' | 'custom';
+  query?: string;
+  /** network segment of the query endpoint's api paths, `testnet3` or `mainnet` */
+  path: string;
+}
+
 export interface DiscoveryResult {
   version: string;
   features: string[];
+  /** `function`s transferv2 takes on this network */
+  transfer_functions?: TransferFunction[];
   pubkey: string;
+  network?: ActiveNetwork;
+}
+
+export interface MethodStats {
+  method: string;
+  count: number;
+  success_rate: number;
+  p50_ms: number;
+  p95_ms: number;
+  max_ms: number;
+  last_error?: string;
+  last_error_at?: string;
+}
+
+export interface StatsResult {
+  version: string;
+  since: string;
+  cores: number;
+  methods: MethodStats[];
+  previous?: StatsResult;
 }
@@ -0,0 +1,87 @@
+//! Key operations wallets would otherwise load snarkvm compiled to wasm for.
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{Address, PrivateKey, Signature, Transaction, ViewKey};
+
+use super::CurrentNetwork;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub private_key: String,
+    pub view_key: String,
+    pub address: String,
+}
+
+pub fn generate_account() -> Result<Account> {
+    let private_key = PrivateKey::<CurrentNetwork>::new(&mut OsRng)?;
+    account(&private_key)
+}
+
+/// view key and address of `private_key`
+pub fn derive_account(private_key: &str) -> Result<Account> {
+    account(&parse_private_key(private_key)?)
+}
+
+/// signature of the utf-8 bytes of `message`
+pub fn sign(private_key: &str, message: &str) -> Result<String> {
+    let private_key = parse_private_key(private_key)?;
+    let signature = private_key.sign_bytes(message.as_bytes(), &mut OsRng)?;
+    Ok(signature.to_string())
+}
+
+pub fn verify(address: &str, message: &str, signature: &str) -> Result<bool> {
+    let address = Address::<CurrentNetwork>::from_str(address).context("invalid address")?;
+    let signature =
+        Signature::<CurrentNetwork>::from_str(signature).context("invalid signature")?;
+    Ok(signature.verify_bytes(&address, message.as_bytes()))
+}
+
+/// records of `transaction` owned by `private_key`, decrypted, in output order
+pub fn owned_records(private_key: &str, transaction: &str) -> Result<Vec<String>> {
+    let view_key = ViewKey::try_from(&parse_private_key(private_key)?)?;
+    let transaction =
+        Transaction::<CurrentNetwork>::from_str(transaction).context("invalid transaction")?;
+    transaction
+        .records()
+        .filter(|(_, record)| record.is_owner(&view_key))
+        .map(|(_, record)| Ok(record.decrypt(&view_key)?.to_string()))
+        .collect()
+}
+
+fn parse_private_key(private_key: &str) -> Result<PrivateKey<CurrentNetwork>> {
+    PrivateKey::from_str(private_key).context("invalid private key")
+}
+
+fn account(private_key: &PrivateKey<CurrentNetwork>) -> Result<Account> {
+    Ok(Account {
+        private_key: private_key.to_string(),
+        view_key: ViewKey::try_from(private_key)?.to_string(),
+        address: Address::try_from(private_key)?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_derive_account() {
+        let account = generate_account().unwrap();
+        assert_eq!(derive_account(&account.private_key).unwrap(), account);
+        assert!(derive_account("APrivateKey1invalid").is_err());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let account = generate_account().unwrap();
+        let signature = sign(&account.private_key, "hello").unwrap();
+        assert!(verify(&account.address, "hello", &signature).unwrap());
+        assert!(!verify(&account.address, "hello!", &signature).unwrap());
+        let other = generate_account().unwrap();
+        assert!(!verify(&other.address, "hello", &signature).unwrap());
+    }
+}
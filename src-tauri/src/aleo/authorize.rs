@@ -0,0 +1,147 @@
+//! Authorizations signed here and proven elsewhere, with
+//! `transaction_from_authorization` and `deploy_from_authorization`.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{Context, Result};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{
+    Authorization, Field, Identifier, Plaintext, PrivateKey, Program, ProgramID, ProgramOwner,
+    Record, Value,
+};
+
+use super::{add_program, with_process, CurrentAleo, CurrentNetwork};
+use crate::rpc::fee::{Fee, FeePayment};
+
+/// what `deploy_from_authorization` takes besides the program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployAuthorization {
+    pub owner: String,
+    pub fee_authorization: String,
+}
+
+/// the first call of an execute authorization
+pub struct Call {
+    pub program_id: String,
+    pub function: String,
+    pub inputs: Vec<String>,
+}
+
+pub fn authorize_execute(
+    private_key: &str,
+    program_id: &str,
+    function: &str,
+    inputs: Vec<String>,
+    endpoints: &[String],
+) -> Result<String> {
+    let private_key = PrivateKey::<CurrentNetwork>::from_str(private_key)?;
+    let program_id = ProgramID::<CurrentNetwork>::from_str(program_id)?;
+    let function = Identifier::<CurrentNetwork>::from_str(function)?;
+    let inputs = inputs
+        .iter()
+        .map(|v| Value::from_str(v))
+        .collect::<Result<Vec<_>>>()?;
+    let authorization = with_process(|process| {
+        add_program(process, &program_id, &HashMap::new(), endpoints)?;
+        process.authorize::<CurrentAleo, _>(
+            &private_key,
+            program_id,
+            function,
+            inputs.into_iter(),
+            &mut OsRng,
+        )
+    })?;
+    Ok(authorization.to_string())
+}
+
+/// fee authorization for the execution `execute_authorization` will become
+pub fn authorize_fee(
+    private_key: &str,
+    execute_authorization: &str,
+    base_fee_in_microcredits: u64,
+    fee: &Fee,
+) -> Result<String> {
+    let private_key = PrivateKey::<CurrentNetwork>::from_str(private_key)?;
+    let execution_id = parse_authorization(execute_authorization)?.to_execution_id()?;
+    let authorization =
+        fee_authorization(&private_key, execution_id, base_fee_in_microcredits, fee)?;
+    Ok(authorization.to_string())
+}
+
+/// owner and fee authorization of a deployment. the deployment is built here to
+/// get its id, which synthesizes the keys of every function of the program
+pub fn authorize_deploy_fee(
+    private_key: &str,
+    program: &str,
+    imports: &HashMap<String, String>,
+    base_fee_in_microcredits: u64,
+    fee: &Fee,
+    endpoints: &[String],
+) -> Result<DeployAuthorization> {
+    let private_key = PrivateKey::<CurrentNetwork>::from_str(private_key)?;
+    let program = Program::<CurrentNetwork>::from_str(program).context("invalid program")?;
+    // on a copy, the shared process stays available while the keys are synthesized
+    let mut process = with_process(|process| Ok(process.clone()))?;
+    for import in program.imports().keys() {
+        add_program(&mut process, import, imports, endpoints)?;
+    }
+    let deployment = process.deploy::<CurrentAleo, _>(&program, &mut OsRng)?;
+    let deployment_id = deployment.to_deployment_id()?;
+    let owner = ProgramOwner::new(&private_key, deployment_id, &mut OsRng)?;
+    let authorization =
+        fee_authorization(&private_key, deployment_id, base_fee_in_microcredits, fee)?;
+    Ok(DeployAuthorization {
+        owner: owner.to_string(),
+        fee_authorization: authorization.to_string(),
+    })
+}
+
+pub fn first_call(execute_authorization: &str) -> Result<Call> {
+    let authorization = parse_authorization(execute_authorization)?;
+    let request = authorization.peek_next()?;
+    Ok(Call {
+        program_id: request.program_id().to_string(),
+        function: request.function_name().to_string(),
+        inputs: request.inputs().iter().map(|v| v.to_string()).collect(),
+    })
+}
+
+pub(super) fn parse_authorization(authorization: &str) -> Result<Authorization<CurrentNetwork>> {
+    Authorization::from_str(authorization).context("invalid authorization")
+}
+
+pub(super) fn fee_authorization(
+    private_key: &PrivateKey<CurrentNetwork>,
+    id: Field<CurrentNetwork>,
+    base_fee_in_microcredits: u64,
+    fee: &Fee,
+) -> Result<Authorization<CurrentNetwork>> {
+    let priority_fee_in_microcredits = fee.priority_amount();
+    match fee.payment {
+        FeePayment::Public => with_process(|process| {
+            process.authorize_fee_public::<CurrentAleo, _>(
+                private_key,
+                base_fee_in_microcredits,
+                priority_fee_in_microcredits,
+                id,
+                &mut OsRng,
+            )
+        }),
+        FeePayment::Private => {
+            let record = fee.record().context("a private fee needs a fee record")?;
+            let record = Record::<CurrentNetwork, Plaintext<CurrentNetwork>>::from_str(record)
+                .context("invalid fee record")?;
+            with_process(|process| {
+                process.authorize_fee_private::<CurrentAleo, _>(
+                    private_key,
+                    record,
+                    base_fee_in_microcredits,
+                    priority_fee_in_microcredits,
+                    id,
+                    &mut OsRng,
+                )
+            })
+        }
+    }
+}
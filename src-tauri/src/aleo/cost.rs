@@ -0,0 +1,167 @@
+//! What the base fee aleowrap estimates is made of.
+//!
+//! aleowrap returns the base fee as a single number. The parts that follow
+//! from the program alone are computed here: the namespace cost from the
+//! program name, and the finalize cost of every transition from the call tree
+//! of the function. Storage is what remains of the base fee.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use snarkvm::{
+    prelude::{CallOperator, Identifier, Instruction, Process, Program, ProgramID},
+    synthesizer::process::cost_in_microcredits,
+};
+
+use super::{add_program, with_process, CurrentNetwork};
+
+// names this long and longer pay the lowest namespace cost, one credit
+const NAMESPACE_BASE_LENGTH: u32 = 10;
+const MICROCREDITS_PER_CREDIT: u64 = 1_000_000;
+// deeper call trees are refused
+const MAX_CALL_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalizeCost {
+    pub program_id: String,
+    pub function: String,
+    pub cost_in_microcredits: u64,
+}
+
+/// base fee split into its parts, all in microcredits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostBreakdown {
+    /// base fee plus priority fee
+    pub total_in_microcredits: u64,
+    pub base_fee_in_microcredits: u64,
+    pub storage_in_microcredits: u64,
+    /// deployments only
+    pub namespace_in_microcredits: u64,
+    /// not charged by testnet3 nodes, always 0 for now
+    pub synthesis_in_microcredits: u64,
+    /// one entry per transition, in execution order
+    pub finalize: Vec<FinalizeCost>,
+    pub priority_fee_in_microcredits: u64,
+}
+
+impl CostBreakdown {
+    fn new(
+        base_fee: u64,
+        namespace: u64,
+        finalize: Vec<FinalizeCost>,
+        priority_fee: u64,
+    ) -> Result<Self> {
+        let finalize_total = finalize.iter().map(|v| v.cost_in_microcredits).sum::<u64>();
+        let storage = base_fee
+            .checked_sub(namespace + finalize_total)
+            .context(format!("base fee {} is below its known parts", base_fee))?;
+        Ok(Self {
+            total_in_microcredits: base_fee + priority_fee,
+            base_fee_in_microcredits: base_fee,
+            storage_in_microcredits: storage,
+            namespace_in_microcredits: namespace,
+            synthesis_in_microcredits: 0,
+            finalize,
+            priority_fee_in_microcredits: priority_fee,
+        })
+    }
+}
+
+/// cost of registering a program name, shorter names cost more
+fn namespace_cost(name: &str) -> u64 {
+    let length = u32::try_from(name.len()).unwrap_or(u32::MAX);
+    10u64.pow(NAMESPACE_BASE_LENGTH.saturating_sub(length)) * MICROCREDITS_PER_CREDIT
+}
+
+pub fn deployment_cost(program: &str, base_fee: u64, priority_fee: u64) -> Result<CostBreakdown> {
+    let program = Program::<CurrentNetwork>::from_str(program).context("invalid program")?;
+    let namespace = namespace_cost(&program.id().name().to_string());
+    CostBreakdown::new(base_fee, namespace, Vec::new(), priority_fee)
+}
+
+pub fn execution_cost(
+    program_id: &str,
+    function: &str,
+    base_fee: u64,
+    priority_fee: u64,
+    endpoints: &[String],
+) -> Result<CostBreakdown> {
+    let program_id = ProgramID::<CurrentNetwork>::from_str(program_id)?;
+    let function = Identifier::<CurrentNetwork>::from_str(function)?;
+    let finalize = with_process(|process| {
+        add_program(process, &program_id, &HashMap::new(), endpoints)?;
+        let mut finalize = Vec::new();
+        finalize_costs(process, &program_id, &function, 0, &mut finalize)?;
+        Ok(finalize)
+    })?;
+    CostBreakdown::new(base_fee, 0, finalize, priority_fee)
+}
+
+/// finalize costs of the transitions a call of `function` makes. a transition
+/// is complete after the calls it makes, so those come first
+fn finalize_costs(
+    process: &Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    function: &Identifier<CurrentNetwork>,
+    depth: usize,
+    costs: &mut Vec<FinalizeCost>,
+) -> Result<()> {
+    if depth > MAX_CALL_DEPTH {
+        return Err(anyhow!(
+            "calls of {}/{} are nested too deep",
+            program_id,
+            function
+        ));
+    }
+    let function = process.get_program(program_id)?.get_function(function)?;
+    for instruction in function.instructions() {
+        if let Instruction::Call(call) = instruction {
+            // calls of closures run inside this transition
+            if let CallOperator::Locator(locator) = call.operator() {
+                finalize_costs(
+                    process,
+                    locator.program_id(),
+                    locator.resource(),
+                    depth + 1,
+                    costs,
+                )?;
+            }
+        }
+    }
+    let cost = match function.finalize_logic() {
+        Some(finalize) => cost_in_microcredits(finalize)?,
+        None => 0,
+    };
+    costs.push(FinalizeCost {
+        program_id: program_id.to_string(),
+        function: function.name().to_string(),
+        cost_in_microcredits: cost,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_namespace_cost() {
+        assert_eq!(namespace_cost("hello_world"), MICROCREDITS_PER_CREDIT);
+        assert_eq!(namespace_cost("helloworld"), MICROCREDITS_PER_CREDIT);
+        assert_eq!(namespace_cost("hello"), 100_000 * MICROCREDITS_PER_CREDIT);
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let finalize = vec![FinalizeCost {
+            program_id: "token.aleo".to_string(),
+            function: "mint".to_string(),
+            cost_in_microcredits: 300,
+        }];
+        let cost = CostBreakdown::new(2_000, 0, finalize, 50).unwrap();
+        assert_eq!(cost.storage_in_microcredits, 1_700);
+        assert_eq!(cost.total_in_microcredits, 2_050);
+        assert!(CostBreakdown::new(100, 1_000, Vec::new(), 0).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+//! Proving and verifying keys of the shared process kept in the data dir, so the
+//! keys of a function are synthesized once instead of after every start.
+//!
+//! Keys are stored per program, edition and source under
+//! `keys/<program id>/<edition>-<source hash>/<function>.{prover,verifier}` and
+//! loaded when the program is added to the shared process. Authorizations,
+//! verification, cost breakdowns and the proving rpcs use that process, only
+//! deployments go through aleowrap and synthesize their keys there.
+//! `credits.aleo` keys ship with snarkvm's parameters and aren't stored here;
+//! prewarming them loads those parameters.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use rand_core::{OsRng, RngCore};
+use snarkvm::prelude::{
+    CallOperator, FromBytes, Identifier, Instruction, Process, Program, ProgramID, ProvingKey,
+    ToBytes, VerifyingKey,
+};
+
+use super::{add_program, with_process, CurrentAleo, CurrentNetwork};
+use crate::{
+    config::{
+        hash,
+        prewarm::{parse_function, PrewarmConfig},
+        profile::PROFILE,
+        Config,
+    },
+    net::programs::ProgramCache,
+};
+
+const KEYS_DIR: &str = "keys";
+const CREDITS_PROGRAM: &str = "credits.aleo";
+
+static PREWARMING: AtomicBool = AtomicBool::new(false);
+
+/// directory of the keys of `program`
+fn key_dir(program: &Program<CurrentNetwork>) -> Result<PathBuf> {
+    let program_id = program.id().to_string();
+    let edition = ProgramCache::open()?
+        .entry(&program_id)?
+        .and_then(|v| v.edition)
+        .unwrap_or_default();
+    let source_hash = hex::encode(&hash(&program.to_string())[..8]);
+    Ok(PROFILE
+        .data_dir()?
+        .join(KEYS_DIR)
+        .join(program_id)
+        .join(format!("{}-{}", edition, source_hash)))
+}
+
+/// loads the stored keys of `program`'s functions into `process`
+pub fn load_keys(
+    process: &mut Process<CurrentNetwork>,
+    program: &Program<CurrentNetwork>,
+) -> Result<()> {
+    if program.id().to_string() == CREDITS_PROGRAM {
+        return Ok(());
+    }
+    let dir = key_dir(program)?;
+    for function in program.functions().keys() {
+        let prover = dir.join(format!("{}.prover", function));
+        let verifier = dir.join(format!("{}.verifier", function));
+        if !prover.exists() || !verifier.exists() {
+            continue;
+        }
+        // a key that can't be read is synthesized and stored again
+        let result = fs::read(&prover)
+            .map_err(anyhow::Error::from)
+            .and_then(|v| ProvingKey::<CurrentNetwork>::from_bytes_le(&v))
+            .and_then(|proving_key| {
+                let verifying_key =
+                    VerifyingKey::<CurrentNetwork>::from_bytes_le(&fs::read(&verifier)?)?;
+                process.insert_proving_key(program.id(), function, proving_key)?;
+                process.insert_verifying_key(program.id(), function, verifying_key)
+            });
+        match result {
+            Ok(()) => log::info!("loaded keys of {}/{}", program.id(), function),
+            Err(e) => log::warn!("cant load keys of {}/{}: {:#}", program.id(), function, e),
+        }
+    }
+    Ok(())
+}
+
+/// synthesizes the keys of `function` unless `process` has them, and stores them
+pub fn warm(
+    process: &mut Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    function: &Identifier<CurrentNetwork>,
+) -> Result<()> {
+    if process
+        .get_stack(program_id)?
+        .contains_proving_key(function)
+    {
+        return Ok(());
+    }
+    process.synthesize_key::<CurrentAleo, _>(program_id, function, &mut OsRng)?;
+    if program_id.to_string() == CREDITS_PROGRAM {
+        return Ok(());
+    }
+
+    let dir = key_dir(process.get_program(program_id)?)?;
+    fs::create_dir_all(&dir).context("cant create keys dir")?;
+    let proving_key = process.get_proving_key(program_id, function)?;
+    let verifying_key = process.get_verifying_key(program_id, function)?;
+    // the proving key goes last, keys are only loaded once both are there
+    write_key(
+        &dir.join(format!("{}.verifier", function)),
+        &verifying_key.to_bytes_le()?,
+    )
+    .context("cant write verifying key")?;
+    write_key(
+        &dir.join(format!("{}.prover", function)),
+        &proving_key.to_bytes_le()?,
+    )
+    .context("cant write proving key")?;
+    Ok(())
+}
+
+/// writes `bytes` to a temporary file first, an interrupted write never leaves part of a key
+fn write_key(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension(format!("{:016x}.tmp", OsRng.next_u64()));
+    fs::write(&tmp, bytes)?;
+    if let Err(e) = fs::rename(&tmp, path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// [`warm`]s `function` and the functions of other programs it calls, proving would
+/// otherwise synthesize the keys of those without storing them. without `synthesize`
+/// a key that isn't loaded or stored is an error, synthesizing may download parameters
+pub fn warm_calls(
+    process: &mut Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    function: &Identifier<CurrentNetwork>,
+    synthesize: bool,
+) -> Result<()> {
+    let calls: Vec<_> = process
+        .get_program(program_id)?
+        .get_function(function)?
+        .instructions()
+        .iter()
+        .filter_map(|v| match v {
+            Instruction::Call(call) => match call.operator() {
+                CallOperator::Locator(locator) => {
+                    Some((*locator.program_id(), *locator.resource()))
+                }
+                CallOperator::Resource(_) => None,
+            },
+            _ => None,
+        })
+        .collect();
+    for (program_id, function) in calls.iter() {
+        warm_calls(process, program_id, function, synthesize)?;
+    }
+    if !synthesize
+        && !process
+            .get_stack(program_id)?
+            .contains_proving_key(function)
+    {
+        return Err(anyhow!(
+            "keys of {}/{} aren't stored, prewarm the function while online",
+            program_id,
+            function
+        ));
+    }
+    warm(process, program_id, function)
+}
+
+/// makes the verifying key of `function` available in `process`, from the key cache when
+/// it has it. snarkvm only synthesizes both keys at once, so a missing one is [`warm`]ed,
+/// which takes as long as proving the function once
+pub fn verifying_key(
+    process: &mut Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    function: &Identifier<CurrentNetwork>,
+) -> Result<()> {
+    if process
+        .get_stack(program_id)?
+        .contains_verifying_key(function)
+    {
+        return Ok(());
+    }
+    if program_id.to_string() != CREDITS_PROGRAM {
+        let path =
+            key_dir(process.get_program(program_id)?)?.join(format!("{}.verifier", function));
+        if path.exists() {
+            let verifying_key = fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|v| VerifyingKey::<CurrentNetwork>::from_bytes_le(&v));
+            match verifying_key {
+                Ok(v) => return process.insert_verifying_key(program_id, function, v),
+                Err(e) => log::warn!(
+                    "cant load verifying key of {}/{}: {:#}",
+                    program_id,
+                    function,
+                    e
+                ),
+            }
+        }
+    }
+    warm(process, program_id, function)
+}
+
+/// computes the keys of `functions`, `<program id>/<function>` each, one after another
+fn prewarm_functions(functions: &[String], endpoints: &[String]) -> Result<()> {
+    for function in functions.iter() {
+        let (program_id, name) = parse_function(function)?;
+        let program_id = ProgramID::<CurrentNetwork>::from_str(program_id)?;
+        let name = Identifier::<CurrentNetwork>::from_str(name)?;
+        // one function at a time, authorizations wait for the process meanwhile
+        let result = with_process(|process| {
+            add_program(process, &program_id, &HashMap::new(), endpoints)?;
+            warm(process, &program_id, &name)
+        });
+        match result {
+            Ok(()) => log::info!("keys of {} are ready", function),
+            Err(e) => log::error!("cant prewarm {}: {:#}", function, e),
+        }
+    }
+    Ok(())
+}
+
+/// runs [`prewarm_functions`] in the background, once at a time
+fn start_prewarm(functions: Vec<String>) -> Result<()> {
+    if PREWARMING.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let endpoints = Config::get_config().query_endpoints(None);
+    let result = thread::Builder::new()
+        .name("key-prewarm".to_string())
+        .spawn(move || {
+            if let Err(e) = prewarm_functions(&functions, &endpoints) {
+                log::error!("cant prewarm keys: {:#}", e);
+            }
+            PREWARMING.store(false, Ordering::SeqCst);
+        });
+    if let Err(e) = result {
+        PREWARMING.store(false, Ordering::SeqCst);
+        return Err(e).context("cant start prewarm");
+    }
+    Ok(())
+}
+
+/// prewarms the configured functions when the startup setting is on
+pub fn setup_prewarm() {
+    let prewarm = match Config::get_config().get_prewarm_config() {
+        Ok(v) => v,
+        Err(e) => {
+            log::warn!("cant read prewarm config: {:#}", e);
+            return;
+        }
+    };
+    if prewarm.on_startup && !prewarm.functions.is_empty() {
+        if let Err(e) = start_prewarm(prewarm.functions) {
+            log::error!("{:#}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_prewarm_config() -> Result<PrewarmConfig, String> {
+    Config::get_config()
+        .get_prewarm_config()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_prewarm_config(prewarm: PrewarmConfig) -> Result<(), String> {
+    Config::get_config()
+        .set_prewarm_config(&prewarm)
+        .map_err(|e| e.to_string())
+}
+
+/// prewarms `functions`, or the configured ones, in the background
+#[tauri::command]
+pub fn prewarm(functions: Option<Vec<String>>) -> Result<(), String> {
+    let functions = match functions {
+        Some(v) => v,
+        None => {
+            Config::get_config()
+                .get_prewarm_config()
+                .map_err(|e| e.to_string())?
+                .functions
+        }
+    };
+    for function in functions.iter() {
+        parse_function(function).map_err(|e| e.to_string())?;
+    }
+    start_prewarm(functions).map_err(|e| e.to_string())
+}
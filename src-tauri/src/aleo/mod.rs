@@ -0,0 +1,100 @@
+//! snarkvm calls the service makes itself, deployments still go through aleowrap.
+
+pub mod account;
+pub mod authorize;
+pub mod cost;
+pub mod keys;
+pub mod parameters;
+pub mod prove;
+pub mod scan;
+pub mod verify;
+
+use std::{collections::HashMap, str::FromStr, sync::Mutex};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use snarkvm::{
+    circuit::AleoV0,
+    prelude::{Identifier, Process, Program, ProgramID, Testnet3},
+};
+
+use crate::net::programs;
+
+pub type CurrentNetwork = Testnet3;
+pub type CurrentAleo = AleoV0;
+
+lazy_static! {
+    static ref PROCESS: Mutex<Option<Process<CurrentNetwork>>> = Mutex::new(None);
+    /// parsed on its own, loading the process would load every credits.aleo key
+    static ref CREDITS: Option<Program<CurrentNetwork>> = match Program::credits() {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::error!("cant parse credits.aleo: {}", e);
+            None
+        }
+    };
+}
+
+/// runs `f` with the shared process, `credits.aleo` is loaded on first use
+pub fn with_process<R>(f: impl FnOnce(&mut Process<CurrentNetwork>) -> Result<R>) -> Result<R> {
+    let mut process = PROCESS.lock().unwrap();
+    if process.is_none() {
+        *process = Some(Process::load().context("cant load process")?);
+    }
+    f(process.as_mut().unwrap())
+}
+
+/// whether the shared process, and with it every `credits.aleo` key, is loaded
+pub fn process_loaded() -> bool {
+    PROCESS.lock().unwrap().is_some()
+}
+
+/// names of the functions of the network's `credits.aleo`
+pub fn credits_functions() -> Vec<String> {
+    CREDITS
+        .as_ref()
+        .map(|v| v.functions().keys().map(|v| v.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// whether the network's `credits.aleo` has `function`
+pub fn is_credits_function(function: &str) -> bool {
+    let function = match Identifier::<CurrentNetwork>::from_str(function) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    CREDITS
+        .as_ref()
+        .map(|v| v.contains_function(&function))
+        .unwrap_or(false)
+}
+
+/// adds `program_id` and everything it imports to `process`. sources come from
+/// `imports` when the caller passed them, otherwise from the program cache
+pub fn add_program(
+    process: &mut Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    imports: &HashMap<String, String>,
+    endpoints: &[String],
+) -> Result<()> {
+    if process.contains_program(program_id) {
+        return Ok(());
+    }
+    let program = match imports.get(&program_id.to_string()) {
+        Some(source) => Program::from_str(source).context(format!("invalid {}", program_id))?,
+        None => fetch_program(program_id, endpoints)?,
+    };
+    for import in program.imports().keys() {
+        add_program(process, import, imports, endpoints)?;
+    }
+    process.add_program(&program)?;
+    keys::load_keys(process, &program)
+}
+
+fn fetch_program(
+    program_id: &ProgramID<CurrentNetwork>,
+    endpoints: &[String],
+) -> Result<Program<CurrentNetwork>> {
+    let source = programs::program(&program_id.to_string(), endpoints)?;
+    Program::from_str(&source).context(format!("invalid {}", program_id))
+}
@@ -0,0 +1,85 @@
+//! snarkvm's parameters on this machine.
+//!
+//! snarkvm downloads the `credits.aleo` proving keys when the shared process is
+//! first loaded and keeps them in `~/.aleo/resources`, aleowrap reads the same
+//! files. Proving from a state snapshot must not reach out, so it fails before
+//! anything is loaded when one of them is missing. Prewarming the `credits.aleo`
+//! functions once while online fills the directory, it can also be copied from
+//! another machine.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::{credits_functions, process_loaded};
+
+const RESOURCES_DIR: &str = ".aleo/resources";
+
+pub fn resources_dir() -> Result<PathBuf> {
+    Ok(dirs_next::home_dir()
+        .context("cant find home_dir")?
+        .join(RESOURCES_DIR))
+}
+
+/// fails unless the shared process can be loaded without downloading anything
+pub fn check_local() -> Result<()> {
+    // a loaded process holds every credits.aleo key already
+    if process_loaded() {
+        return Ok(());
+    }
+    let dir = resources_dir()?;
+    let missing = missing_provers(&dir, &credits_functions())?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "proving keys of credits.aleo/{} aren't in {}, prewarm the credits.aleo functions while online or copy the directory from a machine that did",
+        missing.join(", "),
+        dir.display()
+    ))
+}
+
+/// functions without a proving key in `dir`, snarkvm names them
+/// `<function>.prover.<checksum>`
+fn missing_provers(dir: &Path, functions: &[String]) -> Result<Vec<String>> {
+    let files: Vec<String> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|v| v.ok())
+            .map(|v| v.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).context("cant read parameters dir"),
+    };
+    Ok(functions
+        .iter()
+        .filter(|function| {
+            let prefix = format!("{}.prover.", function);
+            !files.iter().any(|v| v.starts_with(&prefix))
+        })
+        .cloned()
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_provers() {
+        let dir = std::env::temp_dir().join(format!("aleo-acc-params-{}", std::process::id()));
+        let functions = vec!["transfer_public".to_string(), "fee_public".to_string()];
+        assert_eq!(missing_provers(&dir, &functions).unwrap(), functions);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("transfer_public.prover.1a2b3c4"), b"").unwrap();
+        fs::write(dir.join("fee_public.verifier.5d6e7f8"), b"").unwrap();
+        assert_eq!(
+            missing_provers(&dir, &functions).unwrap(),
+            vec!["fee_public".to_string()]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Transactions proven on the shared process.
+//!
+//! Proving here instead of in aleowrap means the keys stored in the key cache
+//! are used, and the keys proving synthesizes are stored for the next start.
+//! The program is added to the shared process, the proof itself runs on a copy
+//! so authorizations and cost estimates don't wait for it. Deployments still go
+//! through aleowrap, they synthesize every key of the program anyway.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use rand_core::OsRng;
+use snarkvm::{
+    ledger::{query::Query, store::helpers::memory::BlockMemory},
+    prelude::{
+        Authorization, Execution, Fee as FeeTransition, Identifier, PrivateKey, Process, ProgramID,
+        Transaction, Value,
+    },
+    synthesizer::process::execution_cost,
+};
+
+use super::{
+    add_program,
+    authorize::{fee_authorization, parse_authorization},
+    keys, with_process, CurrentAleo, CurrentNetwork,
+};
+use crate::rpc::{fee::Fee, transfer::TransferFunction};
+
+const CREDITS_PROGRAM: &str = "credits.aleo";
+
+/// where a proof gets the ledger state and the programs it calls
+pub struct ProvingState {
+    /// query url state roots and state paths are read from, the relay's
+    pub query: String,
+    /// sources to use instead of the program cache, a snapshot's programs
+    pub programs: HashMap<String, String>,
+    /// endpoints programs missing from the cache are fetched from, none for a snapshot
+    pub endpoints: Vec<String>,
+    /// proving from a snapshot, keys that aren't stored are an error instead of synthesized
+    pub offline: bool,
+}
+
+pub fn execute(
+    private_key: &str,
+    program_id: &str,
+    function: &str,
+    inputs: Vec<String>,
+    fee: Option<&Fee>,
+    state: &ProvingState,
+) -> Result<String> {
+    let private_key = PrivateKey::<CurrentNetwork>::from_str(private_key)?;
+    let program_id = ProgramID::<CurrentNetwork>::from_str(program_id)?;
+    let function = Identifier::<CurrentNetwork>::from_str(function)?;
+    let inputs = inputs
+        .iter()
+        .map(|v| Value::from_str(v))
+        .collect::<Result<Vec<_>>>()?;
+
+    let process = process_for(&program_id, &function, state)?;
+    let authorization = process.authorize::<CurrentAleo, _>(
+        &private_key,
+        program_id,
+        function,
+        inputs.into_iter(),
+        &mut OsRng,
+    )?;
+    let execution = prove_execution(&process, authorization, state)?;
+    let fee = match fee {
+        Some(fee) => {
+            let (base_fee, _) = execution_cost(&process, &execution)?;
+            let authorization =
+                fee_authorization(&private_key, execution.to_execution_id()?, base_fee, fee)?;
+            Some(prove_fee(&process, authorization, state)?)
+        }
+        None => None,
+    };
+    Ok(Transaction::from_execution(execution, fee)?.to_string())
+}
+
+/// one of the `credits.aleo` transfer functions
+pub fn transfer(
+    private_key: &str,
+    function: TransferFunction,
+    recipient: &str,
+    amount: u64,
+    input_record: Option<&str>,
+    fee: &Fee,
+    state: &ProvingState,
+) -> Result<String> {
+    let mut inputs = Vec::new();
+    if function.spends_record() {
+        let record = input_record.ok_or_else(|| anyhow!("{} needs a record", function))?;
+        inputs.push(record.to_string());
+    }
+    inputs.push(recipient.to_string());
+    inputs.push(format!("{}u64", amount));
+    execute(
+        private_key,
+        CREDITS_PROGRAM,
+        &function.credits_function(),
+        inputs,
+        Some(fee),
+        state,
+    )
+}
+
+pub fn join(
+    private_key: &str,
+    first_record: &str,
+    second_record: &str,
+    fee: &Fee,
+    state: &ProvingState,
+) -> Result<String> {
+    let inputs = vec![first_record.to_string(), second_record.to_string()];
+    execute(
+        private_key,
+        CREDITS_PROGRAM,
+        "join",
+        inputs,
+        Some(fee),
+        state,
+    )
+}
+
+/// `credits.aleo/split` pays no fee
+pub fn split(private_key: &str, record: &str, amount: u64, state: &ProvingState) -> Result<String> {
+    let inputs = vec![record.to_string(), format!("{}u64", amount)];
+    execute(private_key, CREDITS_PROGRAM, "split", inputs, None, state)
+}
+
+/// proves an authorization from `authorize_execute` and its fee authorization
+pub fn transaction_from_authorization(
+    program_id: &str,
+    execute_authorization: &str,
+    fee_authorization: &str,
+    state: &ProvingState,
+) -> Result<String> {
+    let authorization = parse_authorization(execute_authorization)?;
+    let request = authorization.peek_next()?;
+    let (called, function) = (*request.program_id(), *request.function_name());
+    if called.to_string() != program_id {
+        return Err(anyhow!(
+            "the authorization calls {}, not {}",
+            called,
+            program_id
+        ));
+    }
+
+    let process = process_for(&called, &function, state)?;
+    let execution = prove_execution(&process, authorization, state)?;
+    let fee = prove_fee(&process, parse_authorization(fee_authorization)?, state)?;
+    Ok(Transaction::from_execution(execution, Some(fee))?.to_string())
+}
+
+/// a copy of the shared process with `program_id` and the keys of `function` loaded
+fn process_for(
+    program_id: &ProgramID<CurrentNetwork>,
+    function: &Identifier<CurrentNetwork>,
+    state: &ProvingState,
+) -> Result<Process<CurrentNetwork>> {
+    let mut process = with_process(|process| {
+        add_program(process, program_id, &state.programs, &state.endpoints)?;
+        Ok(process.clone())
+    })?;
+    // stacks share their keys with the shared process, keys synthesized on the copy stay
+    keys::warm_calls(&mut process, program_id, function, !state.offline)?;
+    Ok(process)
+}
+
+fn prove_execution(
+    process: &Process<CurrentNetwork>,
+    authorization: Authorization<CurrentNetwork>,
+    state: &ProvingState,
+) -> Result<Execution<CurrentNetwork>> {
+    let request = authorization.peek_next()?;
+    let locator = format!("{}/{}", request.program_id(), request.function_name());
+    let (_, mut trace) = process.execute::<CurrentAleo>(authorization)?;
+    trace.prepare(query(state))?;
+    trace.prove_execution::<CurrentAleo, _>(&locator, &mut OsRng)
+}
+
+fn prove_fee(
+    process: &Process<CurrentNetwork>,
+    authorization: Authorization<CurrentNetwork>,
+    state: &ProvingState,
+) -> Result<FeeTransition<CurrentNetwork>> {
+    let (_, mut trace) = process.execute::<CurrentAleo>(authorization)?;
+    trace.prepare(query(state))?;
+    trace.prove_fee::<CurrentAleo, _>(&mut OsRng)
+}
+
+fn query(state: &ProvingState) -> Query<CurrentNetwork, BlockMemory<CurrentNetwork>> {
+    Query::from(state.query.as_str())
+}
@@ -0,0 +1,324 @@
+//! Finding the records a view key owns in a range of blocks.
+//!
+//! A scan runs as a background job that saves its progress after every batch
+//! of blocks under `scan:<id>`. The id is derived from the view key and the
+//! range, so calling `scan_records` again with the same arguments resumes the
+//! job instead of starting over, without the view key ever being stored.
+//! Found records are kept encrypted with the config password, per batch under
+//! `scan_records:<id>:<height>`.
+
+use std::{collections::HashSet, str::FromStr, sync::Mutex, thread, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use lazy_static::lazy_static;
+use rocksdb::{Direction, IteratorMode};
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{Block, ViewKey};
+
+use super::CurrentNetwork;
+use crate::{
+    config::{hash, Config},
+    net::{retry, QueryClient},
+};
+
+const SCAN_KEY_PREFIX: &str = "scan:";
+const SCAN_RECORDS_KEY_PREFIX: &str = "scan_records:";
+// most blocks a node returns for one request
+const BLOCKS_PER_REQUEST: u32 = 50;
+const FETCH_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    static ref RUNNING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanJob {
+    pub id: String,
+    pub start_height: u32,
+    /// exclusive
+    pub end_height: u32,
+    /// first block not scanned yet
+    pub next_height: u32,
+    pub endpoints: Vec<String>,
+    pub records_found: usize,
+    pub started_at: String,
+    pub updated_at: String,
+    pub done: bool,
+    /// why the job stopped, it resumes on the next call
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScannedRecord {
+    pub commitment: String,
+    pub height: u32,
+    /// decrypted record
+    pub record: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub job: ScanJob,
+    /// records found so far, all of them once the job is done
+    pub records: Vec<ScannedRecord>,
+    pub running: bool,
+}
+
+impl ScanJob {
+    fn new(id: String, start_height: u32, end_height: u32, endpoints: Vec<String>) -> Self {
+        let now = Local::now().to_rfc3339();
+        Self {
+            id,
+            start_height,
+            end_height,
+            next_height: start_height,
+            endpoints,
+            records_found: 0,
+            started_at: now.clone(),
+            updated_at: now,
+            done: false,
+            error: None,
+        }
+    }
+
+    /// the next range of blocks to fetch, none once the end is reached
+    fn next_batch(&self) -> Option<(u32, u32)> {
+        if self.next_height >= self.end_height {
+            return None;
+        }
+        let end = self
+            .next_height
+            .saturating_add(BLOCKS_PER_REQUEST)
+            .min(self.end_height);
+        Some((self.next_height, end))
+    }
+}
+
+impl Config {
+    fn get_scan_job(&self, id: &str) -> Result<Option<ScanJob>> {
+        let db = self.db.clone().context("cant get db")?;
+        match db
+            .get(format!("{}{}", SCAN_KEY_PREFIX, id))
+            .context("cant read db")?
+        {
+            Some(v) => serde_json::from_slice(&v).context("cant decode scan job"),
+            None => Ok(None),
+        }
+    }
+
+    fn set_scan_job(&self, job: &ScanJob) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        db.put(
+            format!("{}{}", SCAN_KEY_PREFIX, job.id),
+            serde_json::to_vec(job)?,
+        )
+        .context("cant write to db")
+    }
+
+    /// records of every scanned batch of the job, in height order
+    fn get_scanned_records(&self, id: &str) -> Result<Vec<ScannedRecord>> {
+        let db = self.db.clone().context("cant get db")?;
+        let prefix = batch_key(id, None);
+        let mut records = Vec::new();
+        for item in db.iterator(IteratorMode::From(prefix.as_bytes(), Direction::Forward)) {
+            let (key, value) = item.context("cant read db")?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let batch: Vec<ScannedRecord> = serde_json::from_slice(&self.decrypt_value(&value)?)
+                .context("cant decode scanned records")?;
+            records.extend(batch);
+        }
+        Ok(records)
+    }
+
+    /// records found in the batch starting at `height`, a batch scanned again replaces them
+    fn set_scanned_records(&self, id: &str, height: u32, records: &[ScannedRecord]) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        let value = self.encrypt_value(&serde_json::to_vec(records)?)?;
+        db.put(batch_key(id, Some(height)), value)
+            .context("cant write to db")
+    }
+}
+
+/// `scan_records:<id>:<height>`, padded so the batches of a job sort by height.
+/// without a height it is the prefix of all batches of the job
+fn batch_key(id: &str, height: Option<u32>) -> String {
+    match height {
+        Some(v) => format!("{}{}:{:010}", SCAN_RECORDS_KEY_PREFIX, id, v),
+        None => format!("{}{}:", SCAN_RECORDS_KEY_PREFIX, id),
+    }
+}
+
+fn job_id(view_key: &str, start_height: u32, end_height: u32) -> String {
+    let key = format!("{}\n{}\n{}", view_key, start_height, end_height);
+    hex::encode(&hash(&key)[..16])
+}
+
+/// starts or resumes the scan of `start_height..end_height` and returns its progress
+pub fn scan_records(
+    view_key: &str,
+    start_height: u32,
+    end_height: u32,
+    endpoints: Vec<String>,
+) -> Result<ScanResult> {
+    ViewKey::<CurrentNetwork>::from_str(view_key).context("invalid view key")?;
+    if start_height >= end_height {
+        return Err(anyhow!("start height must be below end height"));
+    }
+    let config = Config::get_config();
+    let id = job_id(view_key, start_height, end_height);
+    let mut job = match config.get_scan_job(&id)? {
+        Some(v) => v,
+        None => ScanJob::new(id.clone(), start_height, end_height, endpoints.clone()),
+    };
+
+    let running = match job.done {
+        true => false,
+        false => {
+            if !RUNNING.lock().unwrap().contains(&id) {
+                job.endpoints = endpoints;
+                job.error = None;
+                config.set_scan_job(&job)?;
+                start(job.clone(), view_key.to_string());
+            }
+            true
+        }
+    };
+    Ok(ScanResult {
+        records: config.get_scanned_records(&id)?,
+        job,
+        running,
+    })
+}
+
+fn start(job: ScanJob, view_key: String) {
+    let id = job.id.clone();
+    if !RUNNING.lock().unwrap().insert(id.clone()) {
+        return;
+    }
+    let result = thread::Builder::new()
+        .name("record-scan".to_string())
+        .spawn(move || {
+            let id = job.id.clone();
+            run(job, &view_key);
+            RUNNING.lock().unwrap().remove(&id);
+        });
+    if let Err(e) = result {
+        log::error!("cant start scan {}: {}", id, e);
+        RUNNING.lock().unwrap().remove(&id);
+    }
+}
+
+fn run(mut job: ScanJob, view_key: &str) {
+    log::info!(
+        "scanning blocks {}..{} from {}",
+        job.next_height,
+        job.end_height,
+        job.start_height
+    );
+    let config = Config::get_config();
+    let result = ViewKey::<CurrentNetwork>::from_str(view_key).and_then(|view_key| {
+        let client = QueryClient::new(job.endpoints.clone());
+        while let Some((start, end)) = job.next_batch() {
+            let found = scan_batch(&client, &view_key, start, end)?;
+            if !found.is_empty() {
+                config.set_scanned_records(&job.id, start, &found)?;
+            }
+            job.records_found += found.len();
+            job.next_height = end;
+            job.updated_at = Local::now().to_rfc3339();
+            config.set_scan_job(&job)?;
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => {
+            job.done = true;
+            log::info!("scan {} found {} records", job.id, job.records_found);
+        }
+        Err(e) => {
+            log::error!("scan {} stopped at {}: {:#}", job.id, job.next_height, e);
+            job.error = Some(format!("{:#}", e));
+        }
+    }
+    job.updated_at = Local::now().to_rfc3339();
+    if let Err(e) = config.set_scan_job(&job) {
+        log::error!("cant save scan {}: {:#}", job.id, e);
+    }
+}
+
+/// owned records of the blocks `start..end`
+fn scan_batch(
+    client: &QueryClient,
+    view_key: &ViewKey<CurrentNetwork>,
+    start: u32,
+    end: u32,
+) -> Result<Vec<ScannedRecord>> {
+    let path = Config::get_config().network_path(&format!("/blocks?start={}&end={}", start, end));
+    let response = retry(FETCH_ATTEMPTS, RETRY_DELAY, || {
+        let response = client.request("GET", &path, None)?;
+        match response.is_success() {
+            true => Ok(response),
+            false => Err(anyhow!(
+                "{} responded with status {}",
+                path,
+                response.status
+            )),
+        }
+    })?;
+    let blocks: Vec<Block<CurrentNetwork>> =
+        serde_json::from_slice(&response.body).context("cant decode blocks")?;
+
+    let mut found = Vec::new();
+    for block in blocks.iter() {
+        for (commitment, record) in block.records() {
+            if !record.is_owner(view_key) {
+                continue;
+            }
+            let record = record.decrypt(view_key)?;
+            found.push(ScannedRecord {
+                commitment: commitment.to_string(),
+                height: block.height(),
+                record: record.to_string(),
+            });
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_job_id() {
+        let view_key = "AViewKey1nKB4qtGzFAURCVs4RNbyYHTJTHmkfvNTkkUJXaXnbnyX";
+        assert_eq!(job_id(view_key, 0, 100), job_id(view_key, 0, 100));
+        assert_ne!(job_id(view_key, 0, 100), job_id(view_key, 0, 101));
+        assert!(!job_id(view_key, 0, 100).contains("AViewKey"));
+    }
+
+    #[test]
+    fn test_batch_key() {
+        let prefix = batch_key("id", None);
+        let early = batch_key("id", Some(950));
+        let late = batch_key("id", Some(1000));
+        assert!(early.starts_with(&prefix) && late.starts_with(&prefix));
+        assert!(early < late);
+        assert!(!batch_key("id2", Some(0)).starts_with(&prefix));
+    }
+
+    #[test]
+    fn test_next_batch() {
+        let mut job = ScanJob::new("id".to_string(), 10, 75, Vec::new());
+        assert_eq!(job.next_batch(), Some((10, 60)));
+        job.next_height = 60;
+        assert_eq!(job.next_batch(), Some((60, 75)));
+        job.next_height = 75;
+        assert_eq!(job.next_batch(), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Checking a transaction the way a node would before it is broadcast.
+//!
+//! Transitions are checked one by one against the programs they call, while
+//! the proof of an execution covers all of its transitions at once. Checks run
+//! on a copy of the shared process. Verifying keys come from it and the key
+//! cache, missing ones are synthesized and stored like prewarmed keys.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use snarkvm::prelude::{Fee, Field, Process, Transaction, Transition};
+
+use super::{add_program, keys, with_process, CurrentAleo, CurrentNetwork};
+use crate::rpc::fee::FeePayment;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Check {
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+impl From<Result<()>> for Check {
+    fn from(result: Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                valid: true,
+                error: None,
+            },
+            Err(e) => Self {
+                valid: false,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionReport {
+    pub id: String,
+    pub program_id: String,
+    pub function: String,
+    /// the function exists, inputs and outputs match it and its verifying key is available
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeReport {
+    pub transition_id: String,
+    pub payment: FeePayment,
+    pub base_fee_in_microcredits: Option<u64>,
+    pub priority_fee_in_microcredits: Option<u64>,
+    /// the fee proof verifies and pays for this transaction
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub id: String,
+    /// `deploy` or `execute`
+    pub kind: String,
+    pub valid: bool,
+    pub transitions: Vec<TransitionReport>,
+    /// the execution proof, or the deployment's keys and certificates
+    pub proof: Check,
+    /// the program owner's signature, deployments only
+    pub owner: Option<Check>,
+    pub fee: Option<FeeReport>,
+}
+
+impl VerificationReport {
+    fn is_valid(&self) -> bool {
+        self.transitions.iter().all(|v| v.check.valid)
+            && self.proof.valid
+            && self.owner.as_ref().map(|v| v.valid).unwrap_or(true)
+            && self.fee.as_ref().map(|v| v.check.valid).unwrap_or(true)
+    }
+}
+
+/// checks `transaction` on a copy of the shared process. verifying keys the key cache
+/// doesn't have are synthesized, which takes as long as proving each of those functions
+pub fn verify_transaction(transaction: &str, endpoints: &[String]) -> Result<VerificationReport> {
+    let transaction =
+        Transaction::<CurrentNetwork>::from_str(transaction).context("invalid transaction")?;
+    // checks run on a copy, so synthesizing a missing key doesn't hold up other calls
+    let mut process = with_process(|process| Ok(process.clone()))?;
+    let process = &mut process;
+    let mut report = match &transaction {
+        Transaction::Execute(id, execution, fee) => {
+            let transitions = execution
+                .transitions()
+                .map(|v| check_transition(process, v, endpoints))
+                .collect();
+            let proof = Check::from(process.verify_execution(execution));
+            let fee = match fee {
+                Some(v) => Some(check_fee(process, v, execution.to_execution_id()?)),
+                None => None,
+            };
+            VerificationReport {
+                id: id.to_string(),
+                kind: "execute".to_string(),
+                valid: false,
+                transitions,
+                proof,
+                owner: None,
+                fee,
+            }
+        }
+        Transaction::Deploy(id, owner, deployment, fee) => {
+            let deployment_id = deployment.to_deployment_id()?;
+            let proof = Check::from(
+                deployment
+                    .program()
+                    .imports()
+                    .keys()
+                    .try_for_each(|v| add_program(process, v, &HashMap::new(), endpoints))
+                    .and_then(|_| {
+                        process.verify_deployment::<CurrentAleo, _>(deployment, &mut OsRng)
+                    }),
+            );
+            let owner = Check::from(match owner.verify(deployment_id) {
+                true => Ok(()),
+                false => Err(anyhow!("owner signature doesn't match the deployment")),
+            });
+            VerificationReport {
+                id: id.to_string(),
+                kind: "deploy".to_string(),
+                valid: false,
+                transitions: Vec::new(),
+                proof,
+                owner: Some(owner),
+                fee: Some(check_fee(process, fee, deployment_id)),
+            }
+        }
+        Transaction::Fee(..) => return Err(anyhow!(
+            "fee transactions stand in for rejected ones, only deploy and execute transactions can be verified"
+        )),
+    };
+    report.valid = report.is_valid();
+    Ok(report)
+}
+
+fn check_transition(
+    process: &mut Process<CurrentNetwork>,
+    transition: &Transition<CurrentNetwork>,
+    endpoints: &[String],
+) -> TransitionReport {
+    let program_id = transition.program_id();
+    let function_name = transition.function_name();
+    let result = add_program(process, program_id, &HashMap::new(), endpoints).and_then(|_| {
+        let function = process
+            .get_program(program_id)?
+            .get_function(function_name)
+            .context(format!("{} has no function {}", program_id, function_name))?;
+        if transition.inputs().len() != function.inputs().len() {
+            return Err(anyhow!(
+                "{} inputs, {} takes {}",
+                transition.inputs().len(),
+                function_name,
+                function.inputs().len()
+            ));
+        }
+        if transition.outputs().len() != function.outputs().len() {
+            return Err(anyhow!(
+                "{} outputs, {} returns {}",
+                transition.outputs().len(),
+                function_name,
+                function.outputs().len()
+            ));
+        }
+        keys::verifying_key(process, program_id, function_name)
+    });
+    TransitionReport {
+        id: transition.id().to_string(),
+        program_id: program_id.to_string(),
+        function: function_name.to_string(),
+        check: result.into(),
+    }
+}
+
+fn check_fee(
+    process: &mut Process<CurrentNetwork>,
+    fee: &Fee<CurrentNetwork>,
+    id: Field<CurrentNetwork>,
+) -> FeeReport {
+    let transition = fee.transition();
+    let result = keys::verifying_key(process, transition.program_id(), transition.function_name())
+        .and_then(|_| process.verify_fee(fee, id));
+    FeeReport {
+        transition_id: transition.id().to_string(),
+        payment: match fee.is_fee_private() {
+            true => FeePayment::Private,
+            false => FeePayment::Public,
+        },
+        base_fee_in_microcredits: fee.base_amount().ok(),
+        priority_fee_in_microcredits: fee.priority_amount().ok(),
+        check: result.into(),
+    }
+}
@@ -1,2 +1,8 @@
 pub const APP_NAME: &str = "aleo-acc-service";
 pub const RPC_PORT: u16 = 18340;
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const ENV_DATA_DIR: &str = "ALEO_ACC_DATA_DIR";
+pub const ENV_PROFILE: &str = "ALEO_ACC_PROFILE";
+pub const ENV_PORT: &str = "ALEO_ACC_PORT";
+pub const ENV_LOG: &str = "ALEO_ACC_LOG";
@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+const LOG_RETENTION_KEY: &str = "log_retention";
+const LOG_FORMAT_KEY: &str = "log_format";
+const LOG_CONFIG_KEY: &str = "log_config";
+/// ten years, anything longer keeps rotated files forever anyway
+const MAX_AGE_DAYS: u64 = 3650;
+
+/// global log level plus overrides per target, levels are `off`, `error`,
+/// `warn`, `info`, `debug` or `trace`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogConfig {
+    pub level: String,
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+impl LogConfig {
+    /// parses `info,rpc=debug,aleosdk=warn`, the format of `ALEO_ACC_LOG`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut config = Self::default();
+        for item in spec.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            match item.split_once('=') {
+                Some((target, level)) => {
+                    config
+                        .targets
+                        .insert(target.trim().to_string(), level.trim().to_string());
+                }
+                None => config.level = item.to_string(),
+            }
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        parse_level(&self.level)?;
+        for (target, level) in self.targets.iter() {
+            if target.is_empty() {
+                return Err(anyhow!("log target is empty"));
+            }
+            parse_level(level)?;
+        }
+        Ok(())
+    }
+
+    pub fn level_filter(&self) -> LevelFilter {
+        parse_level(&self.level).unwrap_or(LevelFilter::Info)
+    }
+
+    pub fn target_filters(&self) -> Vec<(String, LevelFilter)> {
+        self.targets
+            .iter()
+            .filter_map(|(target, level)| Some((target.clone(), parse_level(level).ok()?)))
+            .collect()
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter> {
+    LevelFilter::from_str(level.trim()).map_err(|_| anyhow!("invalid log level {}", level))
+}
+
+/// line format of the log files, the log page always shows text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// how long rotated log files are kept around
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRetention {
+    /// the current file is rotated once it grows past this size, in bytes
+    pub max_file_size: u64,
+    /// rotated files kept, the oldest are deleted first
+    pub max_files: usize,
+    /// rotated files older than this are deleted
+    pub max_age_days: u64,
+}
+
+impl Default for LogRetention {
+    fn default() -> Self {
+        Self {
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 10,
+            max_age_days: 14,
+        }
+    }
+}
+
+impl LogRetention {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_file_size < 64 * 1024 {
+            return Err(anyhow!("max file size must be at least 64KiB"));
+        }
+        if self.max_files == 0 || self.max_age_days == 0 {
+            return Err(anyhow!("at least one rotated file must be kept"));
+        }
+        if self.max_age_days > MAX_AGE_DAYS {
+            return Err(anyhow!("max age must be at most {} days", MAX_AGE_DAYS));
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    pub fn get_log_retention(&self) -> Result<LogRetention> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(LOG_RETENTION_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode log retention"),
+            None => Ok(LogRetention::default()),
+        }
+    }
+
+    pub fn set_log_retention(&self, retention: &LogRetention) -> Result<()> {
+        retention.validate()?;
+        let db = self.db.clone().context("cant get db")?;
+        db.put(LOG_RETENTION_KEY, serde_json::to_vec(retention)?)
+            .context("cant write to db")
+    }
+
+    pub fn get_log_format(&self) -> Result<LogFormat> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(LOG_FORMAT_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode log format"),
+            None => Ok(LogFormat::default()),
+        }
+    }
+
+    pub fn set_log_format(&self, format: LogFormat) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        db.put(LOG_FORMAT_KEY, serde_json::to_vec(&format)?)
+            .context("cant write to db")
+    }
+
+    pub fn get_log_config(&self) -> Result<LogConfig> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(LOG_CONFIG_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode log config"),
+            None => Ok(LogConfig::default()),
+        }
+    }
+
+    pub fn set_log_config(&self, log_config: &LogConfig) -> Result<()> {
+        log_config.validate()?;
+        let db = self.db.clone().context("cant get db")?;
+        db.put(LOG_CONFIG_KEY, serde_json::to_vec(log_config)?)
+            .context("cant write to db")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_spec() {
+        let config = LogConfig::parse("warn, rpc=debug,rpc error=off").unwrap();
+        assert_eq!(config.level_filter(), LevelFilter::Warn);
+        assert_eq!(
+            config.target_filters(),
+            vec![
+                ("rpc".to_string(), LevelFilter::Debug),
+                ("rpc error".to_string(), LevelFilter::Off)
+            ]
+        );
+        assert!(LogConfig::parse("rpc=loud").is_err());
+    }
+
+    #[test]
+    fn test_validate_retention() {
+        assert!(LogRetention::default().validate().is_ok());
+        let forever = LogRetention {
+            max_age_days: u64::MAX,
+            ..Default::default()
+        };
+        assert!(forever.validate().is_err());
+    }
+}
@@ -1,15 +1,20 @@
 pub mod consts;
+pub mod logging;
+pub mod network;
+pub mod prewarm;
+pub mod profile;
+pub mod proxy;
+pub mod telemetry;
 
-use std::{
-    env,
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Context, Result};
 use lazy_static::lazy_static;
 use sha2::{Digest, Sha256};
 
-use crate::tls;
+use crate::{net::HttpClient, tls};
+
+use self::proxy::ProxyConfig;
 
 lazy_static! {
     static ref CONFIG: Mutex<Config> = Mutex::new(Config::new());
@@ -54,30 +59,54 @@ pub async fn try_password() -> Result<bool, String> {
 }
 
 #[tauri::command]
-pub fn set_proxy(proxy: String) {
-    if let Ok(()) = Config::get_config().set_proxy(&proxy) {
-        set_proxy_env(&proxy)
-    }
+pub fn set_proxy(proxy: Option<ProxyConfig>) -> Result<(), String> {
+    Config::get_config()
+        .set_proxy_config(proxy.as_ref())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_proxy() -> Result<Option<String>, String> {
+pub fn get_proxy() -> Result<Option<ProxyConfig>, String> {
     let proxy = Config::get_config()
-        .get_proxy()
+        .get_proxy_config()
         .map_err(|e| e.to_string())?;
-    Ok(proxy)
+    Ok(proxy.map(|mut v| {
+        v.password = None;
+        v
+    }))
 }
 
 #[tauri::command]
-pub async fn test_proxy(proxy: String) -> Result<(), String> {
-    let mut easy = curl::easy::Easy::new();
-    easy.proxy(&proxy)
-        .map_err(|e| format!("failed to set proxy: {}", e))?;
-    _ = easy.url("https://cp.cloudflare.com");
-
-    easy.perform()
+pub async fn test_proxy(mut proxy: ProxyConfig) -> Result<(), String> {
+    let config = Config::get_config();
+    if proxy.password.is_none() {
+        if let Ok(Some(saved)) = config.get_proxy_config() {
+            if saved.username == proxy.username {
+                proxy.password = saved.password;
+            }
+        }
+    }
+    let endpoint = config
+        .query_endpoints(None)
+        .into_iter()
+        .next()
+        .ok_or("no query endpoint configured")?;
+
+    let client = HttpClient::with_proxy(Some(proxy));
+    let response = client
+        .get(&format!(
+            "{}{}",
+            endpoint,
+            config.network_path("/latest/height")
+        ))
         .map_err(|e| format!("failed to perform request: {}", e))?;
-    return Ok(());
+    if !response.is_success() {
+        return Err(format!(
+            "{} responded with status {}",
+            endpoint, response.status
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Clone)]
@@ -157,9 +186,7 @@ impl Config {
     }
 
     fn create_db() -> Result<rocksdb::DB> {
-        let dbdir = dirs_next::data_dir()
-            .context("cant find data_dir!")?
-            .join("aleo-acc-service");
+        let dbdir = profile::PROFILE.data_dir()?;
         let mut opts = rocksdb::Options::default();
         opts.create_if_missing(true);
         let db = rocksdb::DB::open(&opts, dbdir).context("cant open db")?;
@@ -213,19 +240,20 @@ impl Config {
         }
     }
 
-    pub fn set_proxy(&self, proxy: &str) -> Result<()> {
-        let db = self.db.clone().context("cant get db")?;
-        if proxy.is_empty() {
-            return db.delete("proxy").context("cant delete proxy");
+    /// encrypts a value with the db password, like the server secret
+    pub fn encrypt_value(&self, value: &[u8]) -> Result<Vec<u8>> {
+        let password = self.password.as_ref().context("database not decrypted")?;
+        match password.unsecure() {
+            "" => Ok(value.to_vec()),
+            v => tls::aes::aes_encode(&hash(v), value),
         }
-        db.put("proxy", proxy).context("cant write to db")
     }
 
-    pub fn get_proxy(&self) -> Result<Option<String>> {
-        let db = self.db.clone().context("cant get db")?;
-        match db.get("proxy").context("cant read db")? {
-            Some(v) => Ok(Some(String::from_utf8(v).unwrap_or_default())),
-            None => Ok(None),
+    pub fn decrypt_value(&self, value: &[u8]) -> Result<Vec<u8>> {
+        let password = self.password.as_ref().context("database not decrypted")?;
+        match password.unsecure() {
+            "" => Ok(value.to_vec()),
+            v => tls::aes::aes_decode(&hash(v), value),
         }
     }
 }
@@ -237,28 +265,14 @@ pub fn hash(str: &str) -> Vec<u8> {
     result.to_vec()
 }
 
-pub async fn init() -> Result<()> {
-    if let Ok(proxy) = Config::get_config().get_proxy() {
-        if proxy.is_some() {
-            set_proxy_env(proxy.unwrap().as_str());
-        }
-    }
-    Ok(())
-}
-
-fn set_proxy_env(proxy: &str) {
-    env::set_var("http_proxy", &proxy);
-    env::set_var("https_proxy", &proxy);
-    env::set_var("all_proxy", &proxy);
-}
-
 #[cfg(test)]
 mod test {
     use std::{env, time::Duration};
 
     use tokio::time::sleep;
 
-    use super::set_proxy_env;
+    use super::proxy::ProxyConfig;
+    use crate::net::HttpClient;
 
     #[test]
     fn test_tokio_env() {
@@ -276,13 +290,12 @@ mod test {
 
     #[test]
     fn test_proxy() {
-        let proxy = "http://127.0.0.1:7890";
-        set_proxy_env(proxy);
-        let mut easy = curl::easy::Easy::new();
-        _ = easy.url("https://www.google.com/generate_204");
-
-        easy.perform()
+        let proxy = ProxyConfig::parse_url("http://127.0.0.1:7890").unwrap();
+        let client = HttpClient::with_proxy(Some(proxy));
+        let response = client
+            .get("https://www.google.com/generate_204")
             .map_err(|e| format!("failed to perform request: {}", e))
             .unwrap();
+        assert!(response.is_success());
     }
 }
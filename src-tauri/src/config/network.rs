@@ -0,0 +1,292 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+const NETWORKS_KEY: &str = "networks";
+const DEFAULT_NETWORK: &str = "testnet";
+/// path segment of networks that don't set one, the only one snarkvm 0.16 talks to
+pub const DEFAULT_NETWORK_PATH: &str = "testnet3";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkKind {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    pub name: String,
+    pub kind: NetworkKind,
+    /// query endpoints in order of preference
+    pub endpoints: Vec<String>,
+    /// network segment of the endpoints' api paths, `/<path>/latest/height`
+    #[serde(default = "default_network_path")]
+    pub path: String,
+}
+
+fn default_network_path() -> String {
+    DEFAULT_NETWORK_PATH.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    pub active: String,
+    pub networks: Vec<NetworkProfile>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_NETWORK.to_string(),
+            networks: vec![
+                NetworkProfile {
+                    name: "mainnet".to_string(),
+                    kind: NetworkKind::Mainnet,
+                    endpoints: vec!["https://api.explorer.aleo.org/v1".to_string()],
+                    path: "mainnet".to_string(),
+                },
+                NetworkProfile {
+                    name: "testnet".to_string(),
+                    kind: NetworkKind::Testnet,
+                    endpoints: vec![
+                        "https://api.explorer.aleo.org/v1".to_string(),
+                        "https://vm.aleo.org/api".to_string(),
+                    ],
+                    path: DEFAULT_NETWORK_PATH.to_string(),
+                },
+                NetworkProfile {
+                    name: "devnet".to_string(),
+                    kind: NetworkKind::Devnet,
+                    endpoints: vec!["http://127.0.0.1:3030".to_string()],
+                    path: DEFAULT_NETWORK_PATH.to_string(),
+                },
+            ],
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn active_network(&self) -> Option<&NetworkProfile> {
+        self.networks.iter().find(|v| v.name == self.active)
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<()> {
+        if !self.networks.iter().any(|v| v.name == name) {
+            return Err(anyhow!("network {} not found", name));
+        }
+        self.active = name.to_string();
+        Ok(())
+    }
+
+    pub fn upsert(&mut self, network: NetworkProfile) -> Result<()> {
+        validate_network(&network)?;
+        match self.networks.iter_mut().find(|v| v.name == network.name) {
+            Some(v) => {
+                if v.kind != network.kind {
+                    return Err(anyhow!("cant change kind of network {}", network.name));
+                }
+                *v = network;
+            }
+            None => {
+                if network.kind != NetworkKind::Custom {
+                    return Err(anyhow!("only custom networks can be added"));
+                }
+                self.networks.push(network);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .networks
+            .iter()
+            .position(|v| v.name == name)
+            .context("network not found")?;
+        if self.networks[index].kind != NetworkKind::Custom {
+            return Err(anyhow!("builtin network {} cant be removed", name));
+        }
+        if self.active == name {
+            return Err(anyhow!("cant remove the active network"));
+        }
+        self.networks.remove(index);
+        Ok(())
+    }
+}
+
+fn validate_network(network: &NetworkProfile) -> Result<()> {
+    if network.name.trim().is_empty() {
+        return Err(anyhow!("network name is empty"));
+    }
+    if network.endpoints.is_empty() {
+        return Err(anyhow!("network {} has no query endpoint", network.name));
+    }
+    for endpoint in network.endpoints.iter() {
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err(anyhow!("invalid query endpoint: {}", endpoint));
+        }
+    }
+    if network.path.is_empty()
+        || !network
+            .path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(anyhow!("invalid network path: {}", network.path));
+    }
+    Ok(())
+}
+
+impl Config {
+    pub fn get_network_config(&self) -> Result<NetworkConfig> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(NETWORKS_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode network config"),
+            None => Ok(NetworkConfig::default()),
+        }
+    }
+
+    pub fn set_network_config(&self, networks: &NetworkConfig) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        let value = serde_json::to_vec(networks)?;
+        db.put(NETWORKS_KEY, value).context("cant write to db")
+    }
+
+    /// `path` under the active network's segment, `/latest/height` is `/testnet3/latest/height`
+    pub fn network_path(&self, path: &str) -> String {
+        let network = match self.get_network_config() {
+            Ok(networks) => networks.active_network().map(|v| v.path.clone()),
+            Err(e) => {
+                log::error!("cant read network config: {}", e);
+                None
+            }
+        };
+        format!("/{}{}", network.unwrap_or_else(default_network_path), path)
+    }
+
+    /// query endpoints to try in order, a query given by the caller always comes first
+    pub fn query_endpoints(&self, query: Option<&str>) -> Vec<String> {
+        let mut endpoints = Vec::new();
+        if let Some(query) = query.filter(|v| !v.is_empty()) {
+            endpoints.push(query.to_string());
+        }
+        match self.get_network_config() {
+            Ok(networks) => {
+                if let Some(network) = networks.active_network() {
+                    endpoints.extend(network.endpoints.iter().cloned());
+                }
+            }
+            Err(e) => log::error!("cant read network config: {}", e),
+        }
+        let mut unique: Vec<String> = Vec::new();
+        for endpoint in endpoints {
+            if !unique.contains(&endpoint) {
+                unique.push(endpoint);
+            }
+        }
+        unique
+    }
+}
+
+#[tauri::command]
+pub fn get_networks() -> Result<NetworkConfig, String> {
+    Config::get_config()
+        .get_network_config()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_active_network(name: String) -> Result<(), String> {
+    let config = Config::get_config();
+    let mut networks = config.get_network_config().map_err(|e| e.to_string())?;
+    networks.set_active(&name).map_err(|e| e.to_string())?;
+    config
+        .set_network_config(&networks)
+        .map_err(|e| e.to_string())?;
+    log::info!("active network set to {}", name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn save_network(network: NetworkProfile) -> Result<(), String> {
+    let config = Config::get_config();
+    let mut networks = config.get_network_config().map_err(|e| e.to_string())?;
+    networks.upsert(network).map_err(|e| e.to_string())?;
+    config
+        .set_network_config(&networks)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn remove_network(name: String) -> Result<(), String> {
+    let config = Config::get_config();
+    let mut networks = config.get_network_config().map_err(|e| e.to_string())?;
+    networks.remove(&name).map_err(|e| e.to_string())?;
+    config
+        .set_network_config(&networks)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_network_config() {
+        let mut networks = NetworkConfig::default();
+        assert_eq!(
+            networks.active_network().unwrap().kind,
+            NetworkKind::Testnet
+        );
+
+        let custom = NetworkProfile {
+            name: "my node".to_string(),
+            kind: NetworkKind::Custom,
+            endpoints: vec!["http://10.0.0.2:3030".to_string()],
+            path: DEFAULT_NETWORK_PATH.to_string(),
+        };
+        networks.upsert(custom.clone()).unwrap();
+        networks.set_active("my node").unwrap();
+        assert_eq!(networks.active_network(), Some(&custom));
+        assert!(networks.remove("my node").is_err());
+
+        networks.set_active("mainnet").unwrap();
+        assert_eq!(networks.active_network().unwrap().path, "mainnet");
+        assert!(networks.remove("testnet").is_err());
+        networks.remove("my node").unwrap();
+        assert!(networks.set_active("my node").is_err());
+    }
+
+    #[test]
+    fn test_default_path() {
+        let network: NetworkProfile = serde_json::from_str(
+            r#"{ "name": "my node", "kind": "custom", "endpoints": ["http://10.0.0.2:3030"] }"#,
+        )
+        .unwrap();
+        assert_eq!(network.path, DEFAULT_NETWORK_PATH);
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        let mut networks = NetworkConfig::default();
+        let result = networks.upsert(NetworkProfile {
+            name: "bad".to_string(),
+            kind: NetworkKind::Custom,
+            endpoints: vec!["127.0.0.1:3030".to_string()],
+            path: DEFAULT_NETWORK_PATH.to_string(),
+        });
+        assert!(result.is_err());
+
+        let result = networks.upsert(NetworkProfile {
+            name: "bad".to_string(),
+            kind: NetworkKind::Custom,
+            endpoints: vec!["http://127.0.0.1:3030".to_string()],
+            path: "testnet3/latest".to_string(),
+        });
+        assert!(result.is_err());
+    }
+}
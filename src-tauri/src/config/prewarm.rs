@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+const PREWARM_KEY: &str = "prewarm";
+
+/// functions whose keys are computed ahead of the first call. aleowrap's proving only
+/// shares the keys of `credits.aleo`, others help the service's own process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrewarmConfig {
+    pub on_startup: bool,
+    /// `<program id>/<function>`
+    pub functions: Vec<String>,
+}
+
+impl Default for PrewarmConfig {
+    fn default() -> Self {
+        Self {
+            on_startup: false,
+            functions: [
+                "transfer_public",
+                "transfer_private",
+                "transfer_public_to_private",
+                "transfer_private_to_public",
+                "fee_public",
+                "fee_private",
+            ]
+            .iter()
+            .map(|v| format!("credits.aleo/{}", v))
+            .collect(),
+        }
+    }
+}
+
+impl PrewarmConfig {
+    pub fn validate(&self) -> Result<()> {
+        for function in self.functions.iter() {
+            parse_function(function)?;
+        }
+        Ok(())
+    }
+}
+
+/// splits `<program id>/<function>`
+pub fn parse_function(function: &str) -> Result<(&str, &str)> {
+    match function.split_once('/') {
+        Some((program, name))
+            if program.ends_with(".aleo") && !name.is_empty() && !name.contains('/') =>
+        {
+            Ok((program, name))
+        }
+        _ => Err(anyhow!(
+            "{} is not a function like credits.aleo/transfer_public",
+            function
+        )),
+    }
+}
+
+impl Config {
+    pub fn get_prewarm_config(&self) -> Result<PrewarmConfig> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(PREWARM_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode prewarm config"),
+            None => Ok(PrewarmConfig::default()),
+        }
+    }
+
+    pub fn set_prewarm_config(&self, prewarm: &PrewarmConfig) -> Result<()> {
+        prewarm.validate()?;
+        let db = self.db.clone().context("cant get db")?;
+        db.put(PREWARM_KEY, serde_json::to_vec(prewarm)?)
+            .context("cant write to db")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_function() {
+        assert_eq!(
+            parse_function("credits.aleo/transfer_public").unwrap(),
+            ("credits.aleo", "transfer_public")
+        );
+        assert!(parse_function("credits.aleo").is_err());
+        assert!(parse_function("credits/transfer_public").is_err());
+        assert!(parse_function("credits.aleo/").is_err());
+        assert!(PrewarmConfig::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context, Result};
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+use super::consts::{APP_NAME, DEFAULT_PROFILE, ENV_DATA_DIR, ENV_PORT, ENV_PROFILE, RPC_PORT};
+
+// file in the profile dir holding the port assigned to a named profile
+const PORT_FILE: &str = "port";
+// named profiles get one of the ports after the default one
+const PROFILE_PORTS: u16 = 100;
+
+lazy_static! {
+    pub static ref PROFILE: Profile = Profile::from_args(env::args().skip(1).collect());
+}
+
+/// The profile this process runs with. Every profile owns its own directory,
+/// so the db, server key, logs and port never leak between profiles.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub base_dir: Option<PathBuf>,
+    pub port: Option<u16>,
+    /// set when the profile or data dir was chosen on the command line or by env,
+    /// such an instance is allowed to run side by side with the default one
+    pub explicit: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub data_dir: String,
+    pub port: u16,
+    pub profiles: Vec<String>,
+}
+
+#[tauri::command]
+pub fn get_profile() -> Result<ProfileInfo, String> {
+    let data_dir = PROFILE.data_dir().map_err(|e| e.to_string())?;
+    let profiles = list_profiles().map_err(|e| e.to_string())?;
+    Ok(ProfileInfo {
+        name: PROFILE.name.clone(),
+        data_dir: data_dir.display().to_string(),
+        port: PROFILE.rpc_port(),
+        profiles,
+    })
+}
+
+#[tauri::command]
+pub fn switch_profile(name: String) -> Result<(), String> {
+    validate_profile_name(&name).map_err(|e| e.to_string())?;
+    if name == PROFILE.name {
+        return Ok(());
+    }
+
+    let exe = tauri::utils::platform::current_exe().map_err(|e| e.to_string())?;
+    let mut command = Command::new(exe);
+    command.arg("--profile").arg(&name);
+    if let Some(dir) = &PROFILE.base_dir {
+        command.arg("--data-dir").arg(dir);
+    }
+    command
+        .spawn()
+        .map_err(|e| format!("failed to start profile {}: {}", name, e))?;
+
+    log::info!("switching to profile {}", name);
+    crate::stats::flush();
+    std::process::exit(0);
+}
+
+impl Profile {
+    pub fn from_args(args: Vec<String>) -> Self {
+        Self::from_args_and_env(args, |key| env::var(key).ok())
+    }
+
+    /// `env` looks up the environment variables, the arguments take precedence
+    fn from_args_and_env(args: Vec<String>, env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut name = None;
+        let mut base_dir = None;
+        let mut port = None;
+
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            let (key, value) = match arg.split_once('=') {
+                Some((k, v)) => (k.to_string(), Some(v.to_string())),
+                None => (arg, None),
+            };
+            match key.as_str() {
+                "--profile" => name = value.or_else(|| iter.next()),
+                "--data-dir" => base_dir = value.or_else(|| iter.next()),
+                "--port" => port = value.or_else(|| iter.next()),
+                _ => {}
+            }
+        }
+
+        let name = name.or_else(|| env(ENV_PROFILE));
+        let base_dir = base_dir.or_else(|| env(ENV_DATA_DIR));
+        let port = port.or_else(|| env(ENV_PORT));
+
+        let explicit = name.is_some() || base_dir.is_some();
+        let name = match name {
+            Some(v) if validate_profile_name(&v).is_ok() => v,
+            Some(v) => {
+                log::error!("invalid profile name '{}', using default profile", v);
+                DEFAULT_PROFILE.to_string()
+            }
+            None => DEFAULT_PROFILE.to_string(),
+        };
+
+        Self {
+            name,
+            base_dir: base_dir.filter(|v| !v.is_empty()).map(PathBuf::from),
+            port: port.and_then(|v| v.parse().ok()),
+            explicit,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.name == DEFAULT_PROFILE
+    }
+
+    fn base_dir(&self) -> Result<PathBuf> {
+        match &self.base_dir {
+            Some(v) => Ok(v.clone()),
+            None => dirs_next::data_dir().context("cant find data_dir!"),
+        }
+    }
+
+    /// directory of this profile, the rocksdb lives at its root so the default
+    /// profile keeps using the location of older versions
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        Ok(self.base_dir()?.join(profile_dir_name(&self.name)))
+    }
+
+    pub fn logs_dir(&self) -> Result<PathBuf> {
+        Ok(self.data_dir()?.join("logs"))
+    }
+
+    pub fn rpc_port(&self) -> u16 {
+        if let Some(port) = self.port {
+            return port;
+        }
+        if self.is_default() {
+            return RPC_PORT;
+        }
+        match self.assigned_port() {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("cant assign a port to profile {}: {:#}", self.name, e);
+                RPC_PORT + 1
+            }
+        }
+    }
+
+    /// port stored in the profile dir. on first use the first port no other profile
+    /// has is stored, so named profiles never share one
+    fn assigned_port(&self) -> Result<u16> {
+        let path = self.data_dir()?.join(PORT_FILE);
+        if let Some(port) = read_port(&path) {
+            return Ok(port);
+        }
+        let base_dir = self.base_dir()?;
+        let taken: Vec<u16> = profile_names(&base_dir)
+            .iter()
+            .filter(|v| **v != self.name)
+            .filter_map(|v| read_port(&base_dir.join(profile_dir_name(v)).join(PORT_FILE)))
+            .collect();
+        let port = free_port(&taken).context("every profile port is taken")?;
+        fs::create_dir_all(self.data_dir()?).context("cant create profile dir")?;
+        fs::write(&path, port.to_string()).context("cant write profile port")?;
+        Ok(port)
+    }
+}
+
+fn read_port(path: &Path) -> Option<u16> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn free_port(taken: &[u16]) -> Option<u16> {
+    (RPC_PORT + 1..=RPC_PORT + PROFILE_PORTS).find(|v| !taken.contains(v))
+}
+
+fn profile_dir_name(name: &str) -> String {
+    if name == DEFAULT_PROFILE {
+        APP_NAME.to_string()
+    } else {
+        format!("{}-{}", APP_NAME, name)
+    }
+}
+
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 32 {
+        return Err(anyhow!("profile name must be 1 to 32 characters"));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "profile name can only contain letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(())
+}
+
+pub fn list_profiles() -> Result<Vec<String>> {
+    let mut profiles = profile_names(&PROFILE.base_dir()?);
+    if !profiles.contains(&PROFILE.name) {
+        profiles.push(PROFILE.name.clone());
+    }
+    profiles[1..].sort();
+    Ok(profiles)
+}
+
+/// the default profile and the profiles with a dir in `base_dir`
+fn profile_names(base_dir: &Path) -> Vec<String> {
+    let prefix = format!("{}-", APP_NAME);
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = fs::read_dir(base_dir) {
+        for entry in entries.flatten() {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(name) = file_name.strip_prefix(&prefix) {
+                if validate_profile_name(name).is_ok() && name != DEFAULT_PROFILE {
+                    profiles.push(name.to_string());
+                }
+            }
+        }
+    }
+    profiles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn profile(v: &[&str]) -> Profile {
+        Profile::from_args_and_env(args(v), |_| None)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let profile = profile(&[
+            "--profile",
+            "testnet",
+            "--data-dir=/tmp/acc",
+            "--port",
+            "18400",
+        ]);
+        assert_eq!(profile.name, "testnet");
+        assert_eq!(profile.base_dir, Some(PathBuf::from("/tmp/acc")));
+        assert_eq!(profile.rpc_port(), 18400);
+        assert!(profile.explicit);
+        assert_eq!(
+            profile.data_dir().unwrap(),
+            PathBuf::from("/tmp/acc/aleo-acc-service-testnet")
+        );
+    }
+
+    #[test]
+    fn test_env() {
+        let env = |key: &str| match key {
+            ENV_PROFILE => Some("devnet".to_string()),
+            ENV_PORT => Some("18500".to_string()),
+            _ => None,
+        };
+        let profile = Profile::from_args_and_env(Vec::new(), env);
+        assert_eq!(profile.name, "devnet");
+        assert_eq!(profile.rpc_port(), 18500);
+        let profile = Profile::from_args_and_env(args(&["--profile", "testnet"]), env);
+        assert_eq!(profile.name, "testnet");
+    }
+
+    #[test]
+    fn test_profile_ports() {
+        let dir = env::temp_dir().join(format!("aleo-acc-profiles-{}", std::process::id()));
+        let dir = dir.to_string_lossy().to_string();
+        let testnet = profile(&["--profile", "testnet", "--data-dir", &dir]);
+        let devnet = profile(&["--profile", "devnet", "--data-dir", &dir]);
+        assert_eq!(profile(&["--data-dir", &dir]).rpc_port(), RPC_PORT);
+        assert_eq!(testnet.rpc_port(), RPC_PORT + 1);
+        assert_eq!(devnet.rpc_port(), RPC_PORT + 2);
+        assert_eq!(testnet.rpc_port(), RPC_PORT + 1);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(free_port(&[RPC_PORT + 1, RPC_PORT + 3]), Some(RPC_PORT + 2));
+        let taken: Vec<u16> = (1..=PROFILE_PORTS).map(|v| RPC_PORT + v).collect();
+        assert_eq!(free_port(&taken), None);
+    }
+
+    #[test]
+    fn test_invalid_profile_name() {
+        assert!(validate_profile_name("../etc").is_err());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("dev_net-2").is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+const PROXY_KEY: &str = "proxy_config";
+const PROXY_CREDENTIALS_KEY: &str = "proxy_credentials";
+// plain proxy url written by older versions
+const LEGACY_PROXY_KEY: &str = "proxy";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyScheme {
+    Http,
+    Https,
+    Socks5,
+    Socks5h,
+}
+
+impl ProxyScheme {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProxyScheme::Http => "http",
+            ProxyScheme::Https => "https",
+            ProxyScheme::Socks5 => "socks5",
+            ProxyScheme::Socks5h => "socks5h",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// never persisted with the rest of the config, it is stored encrypted with
+    /// the db password and only returned to the http clients
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// hosts that are reached directly, `example.com` also matches its subdomains
+    /// and `*` bypasses the proxy for everything
+    #[serde(default)]
+    pub bypass: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn parse_url(url: &str) -> Result<Self> {
+        let (scheme, rest) = url.split_once("://").context("proxy url has no scheme")?;
+        let scheme = match scheme {
+            "http" => ProxyScheme::Http,
+            "https" => ProxyScheme::Https,
+            "socks5" => ProxyScheme::Socks5,
+            "socks5h" => ProxyScheme::Socks5h,
+            _ => return Err(anyhow!("unsupported proxy scheme {}", scheme)),
+        };
+        let rest = rest.trim_end_matches('/');
+        let (host, port) = rest.rsplit_once(':').context("proxy url has no port")?;
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            port: port.parse().context("invalid proxy port")?,
+            username: None,
+            password: None,
+            bypass: Vec::new(),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}://{}:{}", self.scheme.as_str(), self.host, self.port)
+    }
+
+    pub fn bypasses(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.bypass.iter().any(|rule| {
+            let rule = rule.trim().trim_start_matches('.').to_ascii_lowercase();
+            rule == "*" || host == rule || host.ends_with(&format!(".{}", rule))
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.host.trim().is_empty() {
+            return Err(anyhow!("proxy host is empty"));
+        }
+        if self.port == 0 {
+            return Err(anyhow!("proxy port is invalid"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProxyCredentials {
+    password: Option<String>,
+}
+
+impl Config {
+    pub fn get_proxy_config(&self) -> Result<Option<ProxyConfig>> {
+        let db = self.db.clone().context("cant get db")?;
+        let mut proxy: ProxyConfig = match db.get(PROXY_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode proxy config")?,
+            None => match db.get(LEGACY_PROXY_KEY).context("cant read db")? {
+                Some(v) => ProxyConfig::parse_url(&String::from_utf8(v).unwrap_or_default())?,
+                None => return Ok(None),
+            },
+        };
+
+        if let Some(v) = db.get(PROXY_CREDENTIALS_KEY).context("cant read db")? {
+            match self.decrypt_value(&v) {
+                Ok(v) => {
+                    let credentials: ProxyCredentials = serde_json::from_slice(&v)?;
+                    proxy.password = credentials.password;
+                }
+                Err(e) => log::warn!("cant decrypt proxy credentials: {}", e),
+            }
+        }
+        Ok(Some(proxy))
+    }
+
+    /// a proxy without password keeps the stored one, an empty password removes it
+    pub fn set_proxy_config(&self, proxy: Option<&ProxyConfig>) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        let proxy = match proxy {
+            Some(v) => v,
+            None => {
+                db.delete(LEGACY_PROXY_KEY).context("cant delete proxy")?;
+                db.delete(PROXY_CREDENTIALS_KEY)
+                    .context("cant delete proxy")?;
+                return db.delete(PROXY_KEY).context("cant delete proxy");
+            }
+        };
+        proxy.validate()?;
+
+        match proxy.password.as_deref() {
+            Some("") => db
+                .delete(PROXY_CREDENTIALS_KEY)
+                .context("cant delete proxy credentials")?,
+            Some(password) => {
+                let credentials = serde_json::to_vec(&ProxyCredentials {
+                    password: Some(password.to_string()),
+                })?;
+                db.put(PROXY_CREDENTIALS_KEY, self.encrypt_value(&credentials)?)
+                    .context("cant write to db")?;
+            }
+            None => {}
+        }
+        db.put(PROXY_KEY, serde_json::to_vec(proxy)?)
+            .context("cant write to db")?;
+        db.delete(LEGACY_PROXY_KEY).context("cant delete proxy")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_proxy_url() {
+        let proxy = ProxyConfig::parse_url("http://127.0.0.1:7890").unwrap();
+        assert_eq!(proxy.scheme, ProxyScheme::Http);
+        assert_eq!(proxy.host, "127.0.0.1");
+        assert_eq!(proxy.port, 7890);
+        assert_eq!(proxy.url(), "http://127.0.0.1:7890");
+        assert!(ProxyConfig::parse_url("ftp://127.0.0.1:21").is_err());
+    }
+
+    #[test]
+    fn test_bypass() {
+        let mut proxy = ProxyConfig::parse_url("socks5h://10.0.0.1:1080").unwrap();
+        proxy.bypass = vec!["localhost".to_string(), ".aleo.org".to_string()];
+        assert!(proxy.bypasses("localhost"));
+        assert!(proxy.bypasses("api.explorer.aleo.org"));
+        assert!(proxy.bypasses("aleo.org"));
+        assert!(!proxy.bypasses("notaleo.org"));
+        assert!(!proxy.bypasses("example.com"));
+    }
+
+    #[test]
+    fn test_password_not_serialized() {
+        let mut proxy = ProxyConfig::parse_url("http://127.0.0.1:7890").unwrap();
+        proxy.username = Some("user".to_string());
+        proxy.password = Some("secret".to_string());
+        let json = serde_json::to_string(&proxy).unwrap();
+        assert!(!json.contains("secret"));
+    }
+}
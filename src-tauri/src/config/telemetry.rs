@@ -0,0 +1,54 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{consts::APP_NAME, Config};
+
+const TELEMETRY_KEY: &str = "telemetry";
+
+/// otlp/http span export, off unless turned on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// collector url spans are posted to as otlp json
+    pub endpoint: String,
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://127.0.0.1:4318/v1/traces".to_string(),
+            service_name: APP_NAME.to_string(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.endpoint.starts_with("http://") && !self.endpoint.starts_with("https://") {
+            return Err(anyhow!("collector endpoint must be an http url"));
+        }
+        if self.service_name.is_empty() {
+            return Err(anyhow!("service name is empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    pub fn get_telemetry_config(&self) -> Result<TelemetryConfig> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(TELEMETRY_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode telemetry config"),
+            None => Ok(TelemetryConfig::default()),
+        }
+    }
+
+    pub fn set_telemetry_config(&self, telemetry: &TelemetryConfig) -> Result<()> {
+        telemetry.validate()?;
+        let db = self.db.clone().context("cant get db")?;
+        db.put(TELEMETRY_KEY, serde_json::to_vec(telemetry)?)
+            .context("cant write to db")
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use super::{entry::LogEntry, query::LogMatcher};
+
+/// a line as the frontend sees it, `seq` keeps growing across `clear_logs`
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    pub seq: u64,
+    pub line: String,
+}
+
+/// a structured entry with its place in the buffer
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub entry: LogEntry,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogsSince {
+    pub lines: Vec<LogLine>,
+    /// cursor for the next call
+    pub next_seq: u64,
+    /// lines after the cursor were dropped because the buffer was full
+    pub missed: bool,
+}
+
+/// the last `max_lines` log entries, each numbered in the order it was logged
+pub struct LogBuffer {
+    max_lines: usize,
+    next_seq: u64,
+    /// lines before this seq were dropped to make room
+    evicted_until: u64,
+    entries: VecDeque<(u64, LogEntry)>,
+}
+
+impl LogBuffer {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            max_lines,
+            next_seq: 0,
+            evicted_until: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: LogEntry) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.entries.push_back((seq, entry));
+        while self.entries.len() > self.max_lines {
+            if let Some((seq, _)) = self.entries.pop_front() {
+                self.evicted_until = seq + 1;
+            }
+        }
+        seq
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().map(|v| &v.1)
+    }
+
+    /// matching entries in the order they were logged, the newest `limit` of them
+    pub fn query(&self, matcher: &LogMatcher, limit: Option<usize>) -> Vec<LogRecord> {
+        let mut records: Vec<LogRecord> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|v| matcher.matches(&v.1))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(seq, entry)| LogRecord {
+                seq: *seq,
+                entry: entry.clone(),
+            })
+            .collect();
+        records.reverse();
+        records
+    }
+
+    pub fn since(&self, seq: u64) -> LogsSince {
+        let lines = self
+            .entries
+            .iter()
+            .filter(|v| v.0 >= seq)
+            .map(|(seq, entry)| LogLine {
+                seq: *seq,
+                line: entry.to_text(),
+            })
+            .collect();
+        LogsSince {
+            lines,
+            next_seq: self.next_seq,
+            missed: seq < self.evicted_until,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{super::query::LogQuery, *};
+
+    fn entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: "2023-10-20T17:18:14+08:00".to_string(),
+            level: "INFO".to_string(),
+            target: "rpc".to_string(),
+            message: message.to_string(),
+            request_id: None,
+            client: None,
+            method: None,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_after_clear() {
+        let mut buffer = LogBuffer::new(3);
+        buffer.push(entry("a"));
+        buffer.push(entry("b"));
+        let logs = buffer.since(0);
+        assert_eq!(logs.lines.len(), 2);
+        assert_eq!(logs.next_seq, 2);
+
+        buffer.clear();
+        buffer.push(entry("c"));
+        let logs = buffer.since(logs.next_seq);
+        assert_eq!(logs.lines.len(), 1);
+        assert_eq!(logs.lines[0].seq, 2);
+        assert!(logs.lines[0].line.ends_with("- c"));
+        assert!(!logs.missed);
+    }
+
+    #[test]
+    fn test_query_limit_keeps_newest() {
+        let mut buffer = LogBuffer::new(10);
+        for message in ["a", "b", "c", "d"] {
+            buffer.push(entry(message));
+        }
+        let matcher = LogMatcher::new(&LogQuery::default()).unwrap();
+        let records = buffer.query(&matcher, Some(2));
+        assert_eq!(records.iter().map(|v| v.seq).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(records[1].entry.message, "d");
+    }
+
+    #[test]
+    fn test_missed_lines() {
+        let mut buffer = LogBuffer::new(2);
+        for message in ["a", "b", "c", "d"] {
+            buffer.push(entry(message));
+        }
+        let logs = buffer.since(1);
+        assert!(logs.missed);
+        assert_eq!(logs.lines.iter().map(|v| v.seq).collect::<Vec<_>>(), [2, 3]);
+        assert!(!buffer.since(2).missed);
+    }
+}
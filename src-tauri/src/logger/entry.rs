@@ -0,0 +1,134 @@
+use std::{cell::Cell, time::Duration};
+
+use chrono::{DateTime, Local};
+use log::Record;
+use serde::Serialize;
+
+use crate::rpc::context;
+
+use super::redact::redact;
+
+thread_local! {
+    static DURATION: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// attaches `duration` to the lines logged inside `f`
+pub fn with_duration<R>(duration: Duration, f: impl FnOnce() -> R) -> R {
+    DURATION.with(|v| v.set(Some(duration.as_millis() as u64)));
+    let result = f();
+    DURATION.with(|v| v.set(None));
+    result
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl LogEntry {
+    pub fn from_record(record: &Record, time: DateTime<Local>) -> Self {
+        let context = context::current();
+        Self {
+            timestamp: time.to_rfc3339(),
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: redact(&record.args().to_string()).into_owned(),
+            request_id: context.as_ref().map(|v| v.id.clone()),
+            client: context.as_ref().and_then(|v| v.client.clone()),
+            method: context.and_then(|v| v.method),
+            duration_ms: DURATION.with(|v| v.get()),
+        }
+    }
+
+    /// `2023-10-20 17:18:14 :: INFO rpc [1a2b3c4d5e6f7a8b transfer 1520ms] - message`
+    pub fn to_text(&self) -> String {
+        let time = DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|v| v.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| self.timestamp.clone());
+
+        let mut tags = Vec::new();
+        if let Some(v) = &self.request_id {
+            tags.push(v.clone());
+        }
+        if let Some(v) = &self.method {
+            tags.push(v.clone());
+        }
+        if let Some(v) = self.duration_ms {
+            tags.push(format!("{}ms", v));
+        }
+
+        if tags.is_empty() {
+            format!(
+                "{} :: {} {} - {}",
+                time, self.level, self.target, self.message
+            )
+        } else {
+            format!(
+                "{} :: {} {} [{}] - {}",
+                time,
+                self.level,
+                self.target,
+                tags.join(" "),
+                self.message
+            )
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry() -> LogEntry {
+        LogEntry {
+            timestamp: "2023-10-20T17:18:14+08:00".to_string(),
+            level: "INFO".to_string(),
+            target: "aleosdk".to_string(),
+            message: "method 'transfer' took 1520 ms".to_string(),
+            request_id: Some("1a2b3c4d5e6f7a8b".to_string()),
+            client: Some("36604057fe67563e".to_string()),
+            method: Some("transfer".to_string()),
+            duration_ms: Some(1520),
+        }
+    }
+
+    #[test]
+    fn test_text_format() {
+        assert_eq!(
+            entry().to_text(),
+            "2023-10-20 17:18:14 :: INFO aleosdk [1a2b3c4d5e6f7a8b transfer 1520ms] - method 'transfer' took 1520 ms"
+        );
+
+        let mut plain = entry();
+        plain.request_id = None;
+        plain.method = None;
+        plain.duration_ms = None;
+        assert_eq!(
+            plain.to_text(),
+            "2023-10-20 17:18:14 :: INFO aleosdk - method 'transfer' took 1520 ms"
+        );
+    }
+
+    #[test]
+    fn test_json_format() {
+        let value: serde_json::Value = serde_json::from_str(&entry().to_json()).unwrap();
+        assert_eq!(value["request_id"], "1a2b3c4d5e6f7a8b");
+        assert_eq!(value["client"], "36604057fe67563e");
+        assert_eq!(value["duration_ms"], 1520);
+    }
+}
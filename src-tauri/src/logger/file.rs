@@ -0,0 +1,211 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::config::logging::{LogFormat, LogRetention};
+
+use super::entry::LogEntry;
+
+const LOG_FILE_PREFIX: &str = "aleo-acc-service";
+const LOG_FILE_EXT: &str = "log";
+
+/// Appends log lines to `<dir>/aleo-acc-service.log`. The file is rotated when
+/// it grows past the size limit or a new day starts, rotated files are named
+/// after the time they were rotated and cleaned up by the retention policy.
+pub struct FileSink {
+    dir: PathBuf,
+    retention: LogRetention,
+    format: LogFormat,
+    file: File,
+    size: u64,
+    day: NaiveDate,
+}
+
+impl FileSink {
+    pub fn open(dir: PathBuf, retention: LogRetention, format: LogFormat) -> Result<Self> {
+        fs::create_dir_all(&dir).context("cant create log dir")?;
+        let path = current_path(&dir);
+
+        // a file left from an earlier day is rotated before appending to it
+        if let Ok(metadata) = fs::metadata(&path) {
+            let modified: DateTime<Local> = metadata.modified()?.into();
+            if modified.date_naive() != Local::now().date_naive() {
+                fs::rename(&path, rotated_path(&dir, modified))?;
+            }
+        }
+
+        let (file, size) = open_current(&dir)?;
+        let sink = Self {
+            dir,
+            retention,
+            format,
+            file,
+            size,
+            day: Local::now().date_naive(),
+        };
+        sink.cleanup()?;
+        Ok(sink)
+    }
+
+    pub fn set_retention(&mut self, retention: LogRetention) -> Result<()> {
+        self.retention = retention;
+        self.cleanup()
+    }
+
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    pub fn write_entry(&mut self, entry: &LogEntry) -> Result<()> {
+        match self.format {
+            LogFormat::Text => self.write_line(&entry.to_text()),
+            LogFormat::Json => self.write_line(&entry.to_json()),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        let now = Local::now();
+        if self.size >= self.retention.max_file_size || now.date_naive() != self.day {
+            self.rotate(now)?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> Result<()> {
+        self.file.flush()?;
+        fs::rename(current_path(&self.dir), rotated_path(&self.dir, now))
+            .context("cant rotate log file")?;
+        let (file, size) = open_current(&self.dir)?;
+        self.file = file;
+        self.size = size;
+        self.day = now.date_naive();
+        self.cleanup()
+    }
+
+    /// deletes rotated files beyond the retention limits, oldest first
+    fn cleanup(&self) -> Result<()> {
+        let mut rotated = rotated_files(&self.dir)?;
+        rotated.sort();
+        rotated.reverse();
+
+        let max_age = Duration::from_secs(self.retention.max_age_days.saturating_mul(24 * 60 * 60));
+        for (index, path) in rotated.iter().enumerate() {
+            let expired = fs::metadata(path)
+                .and_then(|v| v.modified())
+                .ok()
+                .and_then(|v| SystemTime::now().duration_since(v).ok())
+                .map(|age| age > max_age)
+                .unwrap_or(false);
+            if index >= self.retention.max_files || expired {
+                if let Err(e) = fs::remove_file(path) {
+                    eprintln!("cant remove log file {}: {}", path.display(), e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn current_path(dir: &Path) -> PathBuf {
+    dir.join(format!("{}.{}", LOG_FILE_PREFIX, LOG_FILE_EXT))
+}
+
+fn rotated_path(dir: &Path, time: DateTime<Local>) -> PathBuf {
+    let stamp = time.format("%Y%m%d-%H%M%S");
+    let mut count = 0;
+    loop {
+        let path = dir.join(format!(
+            "{}.{}-{:03}.{}",
+            LOG_FILE_PREFIX, stamp, count, LOG_FILE_EXT
+        ));
+        if !path.exists() {
+            return path;
+        }
+        count += 1;
+    }
+}
+
+fn open_current(dir: &Path) -> Result<(File, u64)> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(current_path(dir))
+        .context("cant open log file")?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+/// rotated log files in `dir`, their names sort by rotation time
+pub fn rotated_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let current = current_path(dir);
+    let prefix = format!("{}.", LOG_FILE_PREFIX);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path != current && name.starts_with(&prefix) && name.ends_with(LOG_FILE_EXT) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("aleo-acc-logs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let dir = temp_dir("size");
+        let retention = LogRetention {
+            max_file_size: 100,
+            max_files: 3,
+            max_age_days: 1,
+        };
+        let mut sink = FileSink::open(dir.clone(), retention, LogFormat::Text).unwrap();
+        for i in 0..50 {
+            sink.write_line(&format!("2023-10-20 17:18:14 :: INFO - line {}", i))
+                .unwrap();
+        }
+
+        assert_eq!(rotated_files(&dir).unwrap().len(), 3);
+        let current = fs::read_to_string(current_path(&dir)).unwrap();
+        assert!(current.ends_with("line 49\n"));
+        assert!(current.len() <= 100 + 45);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reopen_appends() {
+        let dir = temp_dir("reopen");
+        let mut sink =
+            FileSink::open(dir.clone(), LogRetention::default(), LogFormat::Text).unwrap();
+        sink.write_line("first").unwrap();
+        drop(sink);
+
+        let mut sink =
+            FileSink::open(dir.clone(), LogRetention::default(), LogFormat::Text).unwrap();
+        sink.write_line("second").unwrap();
+        assert_eq!(
+            fs::read_to_string(current_path(&dir)).unwrap(),
+            "first\nsecond\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
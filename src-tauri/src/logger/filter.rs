@@ -0,0 +1,87 @@
+use log::{LevelFilter, Metadata};
+
+use crate::config::logging::LogConfig;
+
+/// decides which records reach the logger, the most specific target wins
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    level: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn from_config(config: &LogConfig) -> Self {
+        let mut targets = config.target_filters();
+        // longest first so `rpc::server` is matched before `rpc`
+        targets.sort_by_key(|v| std::cmp::Reverse(v.0.len()));
+        Self {
+            level: config.level_filter(),
+            targets,
+        }
+    }
+
+    pub fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    /// highest level any target may log at, used for `log::set_max_level`
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|v| v.1)
+            .fold(self.level, |max, level| max.max(level))
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(name, _)| target_matches(target, name))
+            .map(|v| v.1)
+            .unwrap_or(self.level)
+    }
+}
+
+/// `target` is `name` itself or one of its `name::` children
+pub fn target_matches(target: &str, name: &str) -> bool {
+    target == name
+        || target
+            .strip_prefix(name)
+            .map(|rest| rest.starts_with("::"))
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use log::{Level, MetadataBuilder};
+
+    use super::*;
+
+    fn enabled(filter: &LogFilter, level: Level, target: &str) -> bool {
+        filter.enabled(&MetadataBuilder::new().level(level).target(target).build())
+    }
+
+    #[test]
+    fn test_target_levels() {
+        let config =
+            LogConfig::parse("warn,rpc=debug,rpc error=off,aleo_acc_service=info").unwrap();
+        let filter = LogFilter::from_config(&config);
+
+        assert!(enabled(&filter, Level::Debug, "rpc"));
+        assert!(!enabled(&filter, Level::Trace, "rpc"));
+        assert!(!enabled(&filter, Level::Error, "rpc error"));
+        assert!(enabled(&filter, Level::Info, "aleo_acc_service::net"));
+        assert!(!enabled(&filter, Level::Info, "aleosdk"));
+        assert!(!enabled(&filter, Level::Info, "rpcx"));
+        assert!(enabled(&filter, Level::Warn, "stdout"));
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+}
@@ -1,61 +1,156 @@
-use std::sync::Mutex;
+pub mod buffer;
+pub mod entry;
+pub mod file;
+pub mod filter;
+pub mod query;
+pub mod redact;
+
+use std::{
+    env,
+    sync::{Mutex, RwLock},
+};
 
 use chrono::Local;
 use lazy_static::lazy_static;
 use log::{Log, Metadata, Record};
 
-lazy_static! {
-    static ref LOGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
-}
+use crate::{
+    config::{
+        consts::ENV_LOG,
+        logging::{LogConfig, LogFormat, LogRetention},
+        profile::PROFILE,
+        Config,
+    },
+    service::events::{self, EVENT_LOG_LINE},
+};
 
-struct MemoryLogger {
-    max_lines: usize,
-}
+use self::{
+    buffer::{LogBuffer, LogLine, LogRecord, LogsSince},
+    entry::LogEntry,
+    file::FileSink,
+    filter::LogFilter,
+    query::{LogMatcher, LogQuery},
+};
 
-impl MemoryLogger {
-    fn new(max_lines: usize) -> Self {
-        MemoryLogger { max_lines }
-    }
+pub use self::entry::with_duration;
+
+/// target of the lines captured from the process stdout
+pub const TARGET_STDOUT: &str = "stdout";
+
+const MAX_LINES: usize = 1000;
+
+lazy_static! {
+    static ref LOGS: Mutex<LogBuffer> = Mutex::new(LogBuffer::new(MAX_LINES));
+    static ref FILE_SINK: Mutex<Option<FileSink>> = Mutex::new(None);
+    static ref LOG_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::default());
 }
 
+struct MemoryLogger;
+
 impl Log for MemoryLogger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        LOG_FILTER.read().unwrap().enabled(metadata)
     }
 
     fn log(&self, record: &Record) {
-        let current_time = Local::now();
-        let formatted_time = current_time.format("%Y-%m-%d %H:%M:%S").to_string();
-        let log_entry = format!(
-            "{} :: {} - {}",
-            formatted_time,
-            record.level(),
-            record.args()
-        );
-        // println!("{}", log_entry);
-        let mut logs = LOGS.lock().unwrap();
-        logs.push(log_entry);
-        if logs.len() > self.max_lines {
-            let excess_lines = logs.len() - self.max_lines;
-            logs.drain(0..excess_lines);
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let log_entry = LogEntry::from_record(record, Local::now());
+        // println!("{}", log_entry.to_text());
+        if let Some(sink) = FILE_SINK.lock().unwrap().as_mut() {
+            if let Err(e) = sink.write_entry(&log_entry) {
+                eprintln!("cant write log file: {:#}", e);
+            }
         }
+
+        let line = log_entry.to_text();
+        // sent under the lock so events go out in seq order
+        let mut logs = LOGS.lock().unwrap();
+        let seq = logs.push(log_entry);
+        events::emit(EVENT_LOG_LINE, &LogLine { seq, line });
     }
 
     fn flush(&self) {}
 }
 
 pub fn setup_logger() {
-    let max_lines = 1000;
-    let logger = Box::new(MemoryLogger::new(max_lines));
-    log::set_boxed_logger(logger).unwrap();
+    log::set_boxed_logger(Box::new(MemoryLogger)).unwrap();
     log::set_max_level(log::LevelFilter::Info);
     log::info!("logger init");
+    setup_file_sink();
+    setup_filter();
 }
 
-#[tauri::command]
-pub fn get_logs() -> Vec<String> {
+fn setup_filter() {
+    // the env var wins over the saved config for this run only
+    let log_config = match env::var(ENV_LOG) {
+        Ok(spec) => LogConfig::parse(&spec)
+            .map_err(|e| log::warn!("ignoring invalid {}: {}", ENV_LOG, e))
+            .ok(),
+        Err(_) => None,
+    };
+    let log_config = log_config.unwrap_or_else(|| {
+        Config::get_config().get_log_config().unwrap_or_else(|e| {
+            log::warn!("cant read log config, using default: {}", e);
+            LogConfig::default()
+        })
+    });
+    apply_log_config(&log_config);
+}
+
+fn apply_log_config(log_config: &LogConfig) {
+    let filter = LogFilter::from_config(log_config);
+    log::set_max_level(filter.max_level());
+    *LOG_FILTER.write().unwrap() = filter;
+}
+
+// opened after the logger is installed, reading the retention policy opens the
+// config db which may log itself
+fn setup_file_sink() {
+    let config = Config::get_config();
+    let retention = match config.get_log_retention() {
+        Ok(v) => v,
+        Err(e) => {
+            log::warn!("cant read log retention, using default: {}", e);
+            LogRetention::default()
+        }
+    };
+    let format = config.get_log_format().unwrap_or_default();
+    let dir = match PROFILE.logs_dir() {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("cant find log dir: {}", e);
+            return;
+        }
+    };
+    match FileSink::open(dir.clone(), retention, format) {
+        Ok(sink) => {
+            *FILE_SINK.lock().unwrap() = Some(sink);
+            log::info!("writing logs to {}", dir.display());
+        }
+        Err(e) => log::error!("cant open log file in {}: {:#}", dir.display(), e),
+    }
+}
+
+/// buffered lines as text, oldest first
+pub fn log_lines() -> Vec<String> {
     let logs = LOGS.lock().unwrap();
-    logs.to_vec()
+    logs.entries().map(|v| v.to_text()).collect()
+}
+
+#[tauri::command]
+pub fn get_logs(query: Option<LogQuery>) -> Result<Vec<LogRecord>, String> {
+    let query = query.unwrap_or_default();
+    let matcher = LogMatcher::new(&query).map_err(|e| format!("{:#}", e))?;
+    Ok(LOGS.lock().unwrap().query(&matcher, query.limit))
+}
+
+/// lines logged at or after `seq`, the ui follows `log-line` events and calls
+/// this to fill any gap
+#[tauri::command]
+pub fn get_logs_since(seq: u64) -> LogsSince {
+    LOGS.lock().unwrap().since(seq)
 }
 
 #[tauri::command]
@@ -63,3 +158,55 @@ pub fn clear_logs() {
     let mut logs = LOGS.lock().unwrap();
     logs.clear();
 }
+
+#[tauri::command]
+pub fn get_log_retention() -> Result<LogRetention, String> {
+    Config::get_config()
+        .get_log_retention()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_log_retention(retention: LogRetention) -> Result<(), String> {
+    Config::get_config()
+        .set_log_retention(&retention)
+        .map_err(|e| e.to_string())?;
+    if let Some(sink) = FILE_SINK.lock().unwrap().as_mut() {
+        sink.set_retention(retention).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_format() -> Result<LogFormat, String> {
+    Config::get_config()
+        .get_log_format()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_log_format(format: LogFormat) -> Result<(), String> {
+    Config::get_config()
+        .set_log_format(format)
+        .map_err(|e| e.to_string())?;
+    if let Some(sink) = FILE_SINK.lock().unwrap().as_mut() {
+        sink.set_format(format);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_config() -> Result<LogConfig, String> {
+    Config::get_config()
+        .get_log_config()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_log_config(log_config: LogConfig) -> Result<(), String> {
+    Config::get_config()
+        .set_log_config(&log_config)
+        .map_err(|e| e.to_string())?;
+    apply_log_config(&log_config);
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use log::Level;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{entry::LogEntry, filter::target_matches};
+
+/// filters for `get_logs`, every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    /// entries at this level or more severe
+    pub level: Option<String>,
+    pub target: Option<String>,
+    /// rpc method the entry was logged for
+    pub method: Option<String>,
+    pub request_id: Option<String>,
+    /// substring of the message, or a regex when `regex` is set
+    pub search: Option<String>,
+    #[serde(default)]
+    pub regex: bool,
+    /// rfc3339 time, inclusive
+    pub from: Option<String>,
+    /// rfc3339 time, exclusive
+    pub to: Option<String>,
+    /// the newest entries are kept when there are more
+    pub limit: Option<usize>,
+}
+
+/// a query checked once and reused for every entry
+pub struct LogMatcher {
+    level: Option<Level>,
+    target: Option<String>,
+    method: Option<String>,
+    request_id: Option<String>,
+    search: Option<Search>,
+    from: Option<DateTime<FixedOffset>>,
+    to: Option<DateTime<FixedOffset>>,
+}
+
+enum Search {
+    Text(String),
+    Regex(Regex),
+}
+
+impl LogMatcher {
+    pub fn new(query: &LogQuery) -> Result<Self> {
+        let level = query
+            .level
+            .as_deref()
+            .map(|v| Level::from_str(v).map_err(|_| anyhow!("invalid log level {}", v)))
+            .transpose()?;
+        let search = match &query.search {
+            Some(v) if v.is_empty() => None,
+            Some(v) if query.regex => Some(Search::Regex(
+                Regex::new(v).context(format!("invalid regex {}", v))?,
+            )),
+            Some(v) => Some(Search::Text(v.clone())),
+            None => None,
+        };
+        Ok(Self {
+            level,
+            target: query.target.clone(),
+            method: query.method.clone(),
+            request_id: query.request_id.clone(),
+            search,
+            from: parse_time(query.from.as_deref())?,
+            to: parse_time(query.to.as_deref())?,
+        })
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(level) = self.level {
+            match Level::from_str(&entry.level) {
+                Ok(v) if v <= level => {}
+                _ => return false,
+            }
+        }
+        if let Some(target) = &self.target {
+            if !target_matches(&entry.target, target) {
+                return false;
+            }
+        }
+        if self.method.is_some() && self.method != entry.method {
+            return false;
+        }
+        if self.request_id.is_some() && self.request_id != entry.request_id {
+            return false;
+        }
+        match &self.search {
+            Some(Search::Text(v)) if !entry.message.contains(v.as_str()) => return false,
+            Some(Search::Regex(v)) if !v.is_match(&entry.message) => return false,
+            _ => {}
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let time = match DateTime::parse_from_rfc3339(&entry.timestamp) {
+                Ok(v) => v,
+                Err(_) => return false,
+            };
+            if self.from.map(|v| time < v).unwrap_or(false)
+                || self.to.map(|v| time >= v).unwrap_or(false)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn parse_time(time: Option<&str>) -> Result<Option<DateTime<FixedOffset>>> {
+    time.map(|v| DateTime::parse_from_rfc3339(v).context(format!("invalid time {}", v)))
+        .transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(level: &str, target: &str, method: Option<&str>, message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: "2023-10-20T17:18:14+08:00".to_string(),
+            level: level.to_string(),
+            target: target.to_string(),
+            message: message.to_string(),
+            request_id: None,
+            client: None,
+            method: method.map(|v| v.to_string()),
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_errors_of_method() {
+        let matcher = LogMatcher::new(&LogQuery {
+            level: Some("warn".to_string()),
+            method: Some("transfer".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let failed = "method: transfer ,code:Server error, msg: no fee record";
+        assert!(matcher.matches(&entry("ERROR", "rpc error", Some("transfer"), failed)));
+        assert!(!matcher.matches(&entry("INFO", "rpc", Some("transfer"), "executing")));
+        assert!(!matcher.matches(&entry("ERROR", "rpc error", Some("execute"), failed)));
+    }
+
+    #[test]
+    fn test_search_and_time() {
+        let matcher = LogMatcher::new(&LogQuery {
+            target: Some("aleosdk".to_string()),
+            search: Some(r"took \d{4,} ms".to_string()),
+            regex: true,
+            from: Some("2023-10-20T17:00:00+08:00".to_string()),
+            to: Some("2023-10-20T18:00:00+08:00".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let slow = "method 'join' took 1520 ms";
+        assert!(matcher.matches(&entry("INFO", "aleosdk", None, slow)));
+        assert!(!matcher.matches(&entry("INFO", "aleosdk", None, "method 'join' took 15 ms")));
+        assert!(!matcher.matches(&entry("INFO", "rpc", None, slow)));
+
+        let matcher = LogMatcher::new(&LogQuery {
+            to: Some("2023-10-20T17:18:14+08:00".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(!matcher.matches(&entry("INFO", "rpc", None, "executing")));
+        assert!(LogMatcher::new(&LogQuery {
+            search: Some("(".to_string()),
+            regex: true,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
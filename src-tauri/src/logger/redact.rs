@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+pub const REDACTED: &str = "[redacted]";
+// stored errors are cut to this many bytes
+const MAX_ERROR_LEN: usize = 500;
+
+lazy_static! {
+    // base58 keys, the prefix is kept so the log still says what was there
+    static ref ACCOUNT_SECRET: Regex =
+        Regex::new(r"(APrivateKey1|AViewKey1|AComputeKey1)[1-9A-HJ-NP-Za-km-z]+").unwrap();
+    // entries of a record plaintext that is printed over several lines
+    static ref PRIVATE_ENTRY: Regex = Regex::new(r"-?[A-Za-z0-9_]+\.private\b").unwrap();
+}
+
+/// Masks aleo secrets in a log message: private, view and compute keys,
+/// record plaintexts and any private entry of a plaintext.
+pub fn redact(message: &str) -> Cow<'_, str> {
+    if !message.contains("Key1") && !message.contains(".private") && !message.contains("_nonce") {
+        return Cow::Borrowed(message);
+    }
+
+    let message = redact_records(message);
+    let message = ACCOUNT_SECRET.replace_all(&message, |caps: &regex::Captures| {
+        format!("{}{}", &caps[1], REDACTED)
+    });
+    let message = PRIVATE_ENTRY.replace_all(&message, format!("{}.private", REDACTED));
+    Cow::Owned(message.into_owned())
+}
+
+/// an error message as it is stored, redacted and cut to [`MAX_ERROR_LEN`] bytes
+pub fn redact_error(message: &str) -> String {
+    let mut error = redact(message).into_owned();
+    if error.len() > MAX_ERROR_LEN {
+        let end = (0..=MAX_ERROR_LEN)
+            .rev()
+            .find(|i| error.is_char_boundary(*i))
+            .unwrap_or(0);
+        error.truncate(end);
+    }
+    error
+}
+
+/// replaces every `{ owner: ..., _nonce: ... }` block, nested structs included
+fn redact_records(message: &str) -> String {
+    let mut output = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(owner) = find_owner(rest) {
+        let start = match rest[..owner].rfind('{') {
+            Some(v) => v,
+            None => break,
+        };
+        let end = match matching_brace(&rest[start..]) {
+            Some(v) => start + v,
+            None => break,
+        };
+        let block = &rest[start..=end];
+        if block.contains("_nonce") {
+            output.push_str(&rest[..start]);
+            output.push_str("{ ");
+            output.push_str(REDACTED);
+            output.push_str(" record }");
+        } else {
+            output.push_str(&rest[..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn find_owner(message: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(index) = message[offset..].find("owner") {
+        let index = offset + index;
+        let after = message[index + 5..].trim_start();
+        if after.starts_with(':') {
+            return Some(index);
+        }
+        offset = index + 5;
+    }
+    None
+}
+
+fn matching_brace(block: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in block.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "APrivateKey1zkp8CZNn3yeCseEtxuVPbDCwSyhGW6yZKUYKfgXmcpoGPWH";
+    const VIEW_KEY: &str = "AViewKey1mSnpFFC8Mj4fXbK5YiWgZ3mjiV8CxA79bYNa8ymUpTrw";
+    const ADDRESS: &str = "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px";
+    const RECORD: &str = "{\n  owner: aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px.private,\n  microcredits: 1500000000000000u64.private,\n  _nonce: 3077450429259593211617823051143573281856129402760267155982965992208217472983group.public\n}";
+
+    #[test]
+    fn test_redact_keys() {
+        let line = format!(
+            "method: transfer ,code:Server error, msg: invalid private key {} for view key {}",
+            PRIVATE_KEY, VIEW_KEY
+        );
+        let redacted = redact(&line);
+        assert!(!redacted.contains("zkp8CZNn3yeCseEtxuVPbDCwSyhGW6yZKUYKfgXmcpoGPWH"));
+        assert!(!redacted.contains("mSnpFFC8Mj4fXbK5YiWgZ3mjiV8CxA79bYNa8ymUpTrw"));
+        assert!(redacted.contains("APrivateKey1[redacted]"));
+        assert!(redacted.contains("AViewKey1[redacted]"));
+    }
+
+    #[test]
+    fn test_redact_debug_dump() {
+        let dump = format!(
+            "Error {{\n    context: \"failed to execute\",\n    source: ParseError(\"{}\"),\n}}",
+            PRIVATE_KEY
+        );
+        assert!(!redact(&dump).contains(PRIVATE_KEY));
+    }
+
+    #[test]
+    fn test_redact_record_plaintext() {
+        let line = format!("decrypted record {} from block 12", RECORD);
+        let redacted = redact(&line);
+        assert_eq!(
+            redacted,
+            "decrypted record { [redacted] record } from block 12"
+        );
+
+        let escaped = format!("{:?}", RECORD);
+        assert!(!redact(&escaped).contains(ADDRESS));
+    }
+
+    #[test]
+    fn test_redact_split_record_lines() {
+        for line in RECORD.lines() {
+            let redacted = redact(line);
+            assert!(!redacted.contains(ADDRESS));
+            assert!(!redacted.contains("1500000000000000u64"));
+        }
+        assert_eq!(
+            redact("  microcredits: 1500000000000000u64.private,"),
+            "  microcredits: [redacted].private,"
+        );
+    }
+
+    #[test]
+    fn test_keep_public_data() {
+        let line = format!(
+            "executing transfer_public to {} amount 100u64.public with record1qyqsq",
+            ADDRESS
+        );
+        assert_eq!(redact(&line), line);
+    }
+
+    #[test]
+    fn test_redact_error() {
+        let error = format!("invalid key {}", PRIVATE_KEY);
+        assert_eq!(redact_error(&error), "invalid key APrivateKey1[redacted]");
+        let long = "é".repeat(MAX_ERROR_LEN);
+        assert_eq!(redact_error(&long).len(), MAX_ERROR_LEN);
+    }
+}
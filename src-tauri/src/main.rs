@@ -2,12 +2,16 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![feature(internal_output_capture)]
 
+mod aleo;
 mod auto_start;
 mod config;
 mod logger;
+mod net;
 mod os;
 mod rpc;
 mod service;
+mod stats;
+mod telemetry;
 mod tls;
 
 use anyhow::Result;
@@ -27,13 +31,29 @@ use tauri::{
     generate_handler, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
 };
 
+use aleo::keys::{get_prewarm_config, prewarm, set_prewarm_config};
 use config::{
-    get_proxy, has_password, input_password, set_password, set_proxy, test_proxy, try_password,
+    get_proxy, has_password, input_password,
+    network::{get_networks, remove_network, save_network, set_active_network},
+    profile::{get_profile, switch_profile, PROFILE},
+    set_password, set_proxy, test_proxy, try_password,
 };
-use logger::{clear_logs, get_logs};
+use logger::{
+    clear_logs, get_log_config, get_log_format, get_log_retention, get_logs, get_logs_since,
+    set_log_config, set_log_format, set_log_retention,
+};
+use net::programs::{list_program_cache, purge_program_cache};
 use os::{is_win11, os_info};
-use rpc::{run_rpc_server, stop_rpc_server};
-use service::app::{get_build_info, get_server_url};
+use rpc::{
+    audit::{clear_audit_records, get_audit_records, get_audit_retention, set_audit_retention},
+    run_rpc_server, stop_rpc_server,
+};
+use service::{
+    app::{get_build_info, get_server_url},
+    diagnostics::export_diagnostics,
+};
+use stats::{get_stats, reset_stats};
+use telemetry::{get_telemetry_config, set_telemetry_config};
 
 const MENU_ITEM_AUTO_START: &str = "Start at login";
 const MENU_ITEM_QUIT: &str = "Quit";
@@ -50,7 +70,9 @@ struct Payload {
 #[tokio::main]
 async fn main() {
     logger::setup_logger();
-    log::info!("app started!");
+    telemetry::setup_telemetry();
+    aleo::keys::setup_prewarm();
+    log::info!("app started with profile '{}'!", PROFILE.name);
 
     #[cfg(target_os = "linux")]
     tauri_plugin_deep_link::prepare("us.engy.focus-repro");
@@ -63,7 +85,7 @@ async fn main() {
         loop {
             let mut output = String::new();
             read_line_until_newline(&mut buf_reader, &mut output).unwrap();
-            log::info!("{}", output);
+            log::info!(target: logger::TARGET_STDOUT, "{}", output);
         }
     });
 
@@ -96,30 +118,62 @@ async fn main() {
         .add_item(copy_addr)
         .add_item(about)
         .add_item(quit);
-    let app = tauri::Builder::default()
-        .invoke_handler(generate_handler![
-            get_logs,
-            clear_logs,
-            stop_rpc_server,
-            run_rpc_server,
-            is_win11,
-            os_info,
-            set_proxy,
-            get_proxy,
-            test_proxy,
-            get_server_url,
-            get_build_info,
-            has_password,
-            input_password,
-            set_password,
-            try_password
-        ])
-        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+    let mut builder = tauri::Builder::default().invoke_handler(generate_handler![
+        get_logs,
+        get_logs_since,
+        clear_logs,
+        stop_rpc_server,
+        run_rpc_server,
+        is_win11,
+        os_info,
+        set_proxy,
+        get_proxy,
+        test_proxy,
+        get_server_url,
+        get_build_info,
+        has_password,
+        input_password,
+        set_password,
+        try_password,
+        get_profile,
+        switch_profile,
+        get_networks,
+        set_active_network,
+        save_network,
+        remove_network,
+        get_log_retention,
+        set_log_retention,
+        get_log_format,
+        set_log_format,
+        get_log_config,
+        set_log_config,
+        export_diagnostics,
+        get_audit_records,
+        clear_audit_records,
+        get_audit_retention,
+        set_audit_retention,
+        get_stats,
+        reset_stats,
+        get_telemetry_config,
+        set_telemetry_config,
+        list_program_cache,
+        purge_program_cache,
+        get_prewarm_config,
+        set_prewarm_config,
+        prewarm
+    ]);
+
+    // instances started with an explicit profile or data dir may run side by side
+    if !PROFILE.explicit {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
             println!("{}, {argv:?}, {cwd}", app.package_info().name);
 
             app.emit_all("single-instance", Payload { args: argv, cwd })
                 .unwrap();
-        }))
+        }));
+    }
+
+    let app = builder
         .system_tray(SystemTray::new().with_menu(system_tray_menu))
         .setup(|app| {
 
@@ -195,6 +249,7 @@ async fn main() {
                 }
                 MENU_ITEM_QUIT => {
                     println!("system tray received quit");
+                    stats::flush();
                     std::process::exit(0);
                 }
                 MENU_ITEM_ABOUT => {
@@ -297,14 +352,17 @@ async fn main() {
     }
 
     #[cfg(target_os = "macos")]
-    if let Ok(true) = is_another_instance_running(&app.config().tauri.bundle.identifier) {
-        return;
+    if !PROFILE.explicit {
+        if let Ok(true) = is_another_instance_running(&app.config().tauri.bundle.identifier) {
+            return;
+        }
     }
 
-    _ = config::init();
-
-    #[allow(unused)]
-    app.run(|app, event| {});
+    app.run(|_, event| {
+        if let tauri::RunEvent::Exit = event {
+            stats::flush();
+        }
+    });
 }
 
 #[cfg(target_os = "macos")]
@@ -0,0 +1,343 @@
+//! Program sources fetched from the query endpoints, kept in the data dir.
+//!
+//! Sources are stored by content under `programs/<sha256>.aleo` and indexed by
+//! program id in the config db under `program:<id>`. A cached program is used
+//! as long as the node reports the same edition for it; when the node can't be
+//! asked, or doesn't know editions, the cached source is used as it is.
+//!
+//! Besides the rpcs, the relay answers aleowrap's program fetches from here.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use rocksdb::{Direction, IteratorMode, DB};
+use serde::{Deserialize, Serialize};
+
+use super::QueryClient;
+use crate::config::{hash, profile::PROFILE, Config};
+
+const PROGRAM_KEY_PREFIX: &str = "program:";
+const PROGRAMS_DIR: &str = "programs";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramEntry {
+    pub program_id: String,
+    /// sha256 of the source, the name of its file
+    pub hash: String,
+    /// none when the node didn't report one
+    pub edition: Option<u16>,
+    pub size: usize,
+    pub fetched_at: String,
+}
+
+/// programs kept in the data dir, indexed in the config db
+pub struct ProgramCache {
+    db: Arc<DB>,
+    dir: PathBuf,
+}
+
+impl ProgramCache {
+    pub fn open() -> Result<Self> {
+        let db = Config::get_config().db.context("cant get db")?;
+        let dir = PROFILE.data_dir()?.join(PROGRAMS_DIR);
+        Ok(Self { db, dir })
+    }
+
+    pub fn get(&self, program_id: &str) -> Result<Option<(ProgramEntry, String)>> {
+        let entry = match self.entry(program_id)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        // a file gone or changed on disk is fetched again
+        let source = match fs::read_to_string(self.path(&entry.hash)) {
+            Ok(v) if source_hash(&v) == entry.hash => v,
+            _ => return Ok(None),
+        };
+        Ok(Some((entry, source)))
+    }
+
+    pub fn insert(
+        &self,
+        program_id: &str,
+        source: &str,
+        edition: Option<u16>,
+    ) -> Result<ProgramEntry> {
+        let entry = ProgramEntry {
+            program_id: program_id.to_string(),
+            hash: source_hash(source),
+            edition,
+            size: source.len(),
+            fetched_at: Local::now().to_rfc3339(),
+        };
+        let old = self.entry(program_id)?;
+        fs::create_dir_all(&self.dir).context("cant create programs dir")?;
+        fs::write(self.path(&entry.hash), source).context("cant write program")?;
+        self.db
+            .put(entry_key(program_id), serde_json::to_vec(&entry)?)
+            .context("cant write to db")?;
+        if let Some(old) = old.filter(|v| v.hash != entry.hash) {
+            self.remove_unused(&old.hash)?;
+        }
+        Ok(entry)
+    }
+
+    pub fn list(&self) -> Result<Vec<ProgramEntry>> {
+        let mut entries = Vec::new();
+        let iter = self.db.iterator(IteratorMode::From(
+            PROGRAM_KEY_PREFIX.as_bytes(),
+            Direction::Forward,
+        ));
+        for item in iter {
+            let (key, value) = item.context("cant read db")?;
+            if !key.starts_with(PROGRAM_KEY_PREFIX.as_bytes()) {
+                break;
+            }
+            entries.push(serde_json::from_slice(&value).context("cant decode program entry")?);
+        }
+        Ok(entries)
+    }
+
+    /// removes `program_id`, or every program when none is given. returns how many were removed
+    pub fn purge(&self, program_id: Option<&str>) -> Result<usize> {
+        let entries = match program_id {
+            Some(id) => self
+                .list()?
+                .into_iter()
+                .filter(|v| v.program_id == id)
+                .collect(),
+            None => self.list()?,
+        };
+        for entry in entries.iter() {
+            self.db
+                .delete(entry_key(&entry.program_id))
+                .context("cant delete from db")?;
+            self.remove_unused(&entry.hash)?;
+        }
+        Ok(entries.len())
+    }
+
+    pub fn entry(&self, program_id: &str) -> Result<Option<ProgramEntry>> {
+        match self.db.get(entry_key(program_id)).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode program entry"),
+            None => Ok(None),
+        }
+    }
+
+    fn path(&self, hash: &str) -> PathBuf {
+        self.dir.join(format!("{}.aleo", hash))
+    }
+
+    /// deletes the file of `hash` unless another program has the same source
+    fn remove_unused(&self, hash: &str) -> Result<()> {
+        if self.list()?.iter().any(|v| v.hash == hash) {
+            return Ok(());
+        }
+        match fs::remove_file(self.path(hash)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).context("cant delete program")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn entry_key(program_id: &str) -> String {
+    format!("{}{}", PROGRAM_KEY_PREFIX, program_id)
+}
+
+fn source_hash(source: &str) -> String {
+    hex::encode(hash(source))
+}
+
+/// source of `program_id`, from the cache unless the node has a newer edition
+pub fn program(program_id: &str, endpoints: &[String]) -> Result<String> {
+    let cache = ProgramCache::open()?;
+    let client = QueryClient::new(endpoints.to_vec());
+    let cached = cache.get(program_id)?;
+    let edition = match fetch_edition(&client, program_id) {
+        Ok(v) => v,
+        Err(e) => match cached {
+            Some((_, source)) => {
+                log::warn!(
+                    "cant check edition of {}, using cached: {:#}",
+                    program_id,
+                    e
+                );
+                return Ok(source);
+            }
+            None => None,
+        },
+    };
+    if let Some((entry, source)) = cached {
+        if edition.is_none() || entry.edition == edition {
+            return Ok(source);
+        }
+        log::info!(
+            "{} edition changed from {:?} to {:?}",
+            program_id,
+            entry.edition,
+            edition
+        );
+    }
+
+    let path = Config::get_config().network_path(&format!("/program/{}", program_id));
+    let response = client.request("GET", &path, None)?;
+    if !response.is_success() {
+        return Err(anyhow!(
+            "cant fetch {}, status {}",
+            program_id,
+            response.status
+        ));
+    }
+    let source: String =
+        serde_json::from_slice(&response.body).context(format!("cant decode {}", program_id))?;
+    cache.insert(program_id, &source, edition)?;
+    Ok(source)
+}
+
+/// edition the node reports for `program_id`, none from nodes without editions
+fn fetch_edition(client: &QueryClient, program_id: &str) -> Result<Option<u16>> {
+    let path =
+        Config::get_config().network_path(&format!("/program/{}/latest_edition", program_id));
+    let response = client.request("GET", &path, None)?;
+    match response.is_success() {
+        true => Ok(Some(
+            serde_json::from_slice(&response.body).context("cant decode edition")?,
+        )),
+        false => Ok(None),
+    }
+}
+
+/// `imports` completed with everything `program` imports, directly or not.
+/// sources the caller passed are kept, the others come from [`program`]
+pub fn resolve_imports(
+    program: &str,
+    imports: HashMap<String, String>,
+    endpoints: &[String],
+) -> Result<HashMap<String, String>> {
+    let mut resolved = imports;
+    let mut seen = HashSet::new();
+    let mut pending = import_ids(program);
+    while let Some(id) = pending.pop() {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        let source = match resolved.get(&id) {
+            Some(v) => v.clone(),
+            None => self::program(&id, endpoints)?,
+        };
+        pending.extend(import_ids(&source));
+        resolved.insert(id, source);
+    }
+    Ok(resolved)
+}
+
+/// ids in the `import` statements at the top of a program
+fn import_ids(source: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for line in source.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line.starts_with("program ") {
+            break;
+        }
+        if let Some(id) = line
+            .strip_prefix("import ")
+            .and_then(|v| v.strip_suffix(';'))
+        {
+            ids.push(id.trim().to_string());
+        }
+    }
+    ids
+}
+
+#[tauri::command]
+pub fn list_program_cache() -> Result<Vec<ProgramEntry>, String> {
+    ProgramCache::open()
+        .and_then(|v| v.list())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn purge_program_cache(program_id: Option<String>) -> Result<usize, String> {
+    ProgramCache::open()
+        .and_then(|v| v.purge(program_id.as_deref()))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_cache(name: &str) -> ProgramCache {
+        let dir = std::env::temp_dir().join(format!("aleo-acc-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ProgramCache {
+            db: Arc::new(DB::open_default(dir.join("db")).unwrap()),
+            dir: dir.join(PROGRAMS_DIR),
+        }
+    }
+
+    #[test]
+    fn test_import_ids() {
+        let source = "// token\nimport credits.aleo;\nimport  token.aleo ; // base\n\nprogram swap.aleo;\n\nfunction main:\n";
+        assert_eq!(import_ids(source), vec!["credits.aleo", "token.aleo"]);
+        assert!(import_ids("program hello.aleo;\n").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_passed_imports() {
+        let imports = HashMap::from([
+            (
+                "token.aleo".to_string(),
+                "program token.aleo;\n".to_string(),
+            ),
+            (
+                "swap.aleo".to_string(),
+                "import token.aleo;\nprogram swap.aleo;\n".to_string(),
+            ),
+        ]);
+        let resolved = resolve_imports(
+            "import swap.aleo;\nprogram app.aleo;\n",
+            imports.clone(),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(resolved, imports);
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = temp_cache("programs");
+        let source = "program hello.aleo;\n";
+        let entry = cache.insert("hello.aleo", source, Some(0)).unwrap();
+        cache.insert("copy.aleo", source, None).unwrap();
+        assert_eq!(
+            cache.get("hello.aleo").unwrap(),
+            Some((entry.clone(), source.to_string()))
+        );
+        assert_eq!(cache.list().unwrap().len(), 2);
+
+        // the file stays while another program has the same source
+        assert_eq!(cache.purge(Some("hello.aleo")).unwrap(), 1);
+        assert!(cache.get("hello.aleo").unwrap().is_none());
+        assert!(cache.path(&entry.hash).exists());
+        assert_eq!(cache.purge(None).unwrap(), 1);
+        assert!(!cache.path(&entry.hash).exists());
+    }
+
+    #[test]
+    fn test_changed_file_is_ignored() {
+        let cache = temp_cache("programs-changed");
+        let entry = cache
+            .insert("hello.aleo", "program hello.aleo;\n", None)
+            .unwrap();
+        fs::write(cache.path(&entry.hash), "program other.aleo;\n").unwrap();
+        assert!(cache.get("hello.aleo").unwrap().is_none());
+    }
+}
@@ -0,0 +1,381 @@
+//! Loopback relay for the query endpoint handed to aleowrap.
+//!
+//! aleowrap fetches state roots, state paths and programs from `query` with its
+//! own http client, which knows nothing about our proxy settings or endpoint
+//! order. Instead of the node url it gets `http://127.0.0.1:<port>/<token>`,
+//! and the relay forwards every request through a [`QueryClient`].
+//!
+//! While spans are exported each traced rpc request gets its own token, so the
+//! fetches aleowrap makes are recorded under the request's span.
+//!
+//! snarkvm asks for `/testnet3/...` paths whatever the network, the relay swaps the
+//! network segment for the active network's before forwarding.
+//!
+//! Requests that bring a [`StateSnapshot`] get a token answered from the
+//! snapshot alone, nothing is forwarded for them.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, StatusCode,
+};
+use lazy_static::lazy_static;
+use rand_core::{OsRng, RngCore};
+
+use super::{programs, snapshot::StateSnapshot, HttpResponse, QueryClient};
+use crate::{
+    config::{hash, Config},
+    rpc::context,
+    telemetry::{Span, SpanContext, SpanKind},
+};
+
+// routes of each kind kept at most, the least recently registered are dropped first
+const MAX_ENDPOINT_ROUTES: usize = 64;
+const MAX_TRACED_ROUTES: usize = 256;
+const MAX_SNAPSHOT_ROUTES: usize = 32;
+
+#[derive(Debug, Clone)]
+enum Upstream {
+    /// the endpoints and the path segment of their network
+    Endpoints(Vec<String>, String),
+    Snapshot(Arc<StateSnapshot>),
+}
+
+#[derive(Debug, Clone)]
+struct Route {
+    upstream: Upstream,
+    trace: Option<SpanContext>,
+}
+
+lazy_static! {
+    static ref RELAY_ADDR: Option<SocketAddr> = match start_relay() {
+        Ok(v) => Some(v),
+        Err(e) => {
+            log::error!("cant start query relay: {:#}", e);
+            None
+        }
+    };
+    /// only endpoint lists registered by the service itself are forwarded, so
+    /// the relay can't be used as an open proxy by other local processes
+    static ref ROUTES: Mutex<HashMap<String, Route>> = Mutex::new(HashMap::new());
+    /// keeps endpoint tokens from being derived from the endpoints alone, which are public
+    static ref ROUTE_SALT: [u8; 16] = {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    };
+    static ref ENDPOINT_ROUTES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+    static ref TRACED_ROUTES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+    static ref SNAPSHOT_ROUTES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+}
+
+/// query url to pass to aleowrap, falls back to the first endpoint when the
+/// relay isn't running
+pub fn relay_url(endpoints: Vec<String>) -> Option<String> {
+    if endpoints.is_empty() {
+        return None;
+    }
+    let addr = match *RELAY_ADDR {
+        Some(v) => v,
+        None => return endpoints.first().cloned(),
+    };
+    let trace = context::current().and_then(|v| v.trace);
+    let network = Config::get_config().network_path("");
+    let mut key = format!(
+        "{}\n{}\n{}",
+        hex::encode(*ROUTE_SALT),
+        network,
+        endpoints.join("\n")
+    );
+    if let Some(trace) = trace {
+        key = format!("{}\n{}", key, trace.traceparent());
+    }
+    let token = hex::encode(&hash(&key)[..16]);
+
+    let route = Route {
+        upstream: Upstream::Endpoints(endpoints, network),
+        trace,
+    };
+    match trace {
+        Some(_) => add_route(token.clone(), route, &TRACED_ROUTES, MAX_TRACED_ROUTES),
+        None => add_route(token.clone(), route, &ENDPOINT_ROUTES, MAX_ENDPOINT_ROUTES),
+    }
+    Some(format!("http://{}/{}", addr, token))
+}
+
+/// query url answering from `snapshot` only. unlike [`relay_url`] there is no
+/// fallback, without the relay the request can't be proven offline
+pub fn snapshot_url(snapshot: StateSnapshot) -> Result<String> {
+    snapshot.validate()?;
+    let addr = (*RELAY_ADDR).context("query relay isn't running")?;
+    // random, a snapshot may hold state the caller doesn't want other processes to read
+    let mut token = [0u8; 16];
+    OsRng.fill_bytes(&mut token);
+    let token = hex::encode(token);
+    let route = Route {
+        upstream: Upstream::Snapshot(Arc::new(snapshot)),
+        trace: None,
+    };
+    add_route(token.clone(), route, &SNAPSHOT_ROUTES, MAX_SNAPSHOT_ROUTES);
+    Ok(format!("http://{}/{}", addr, token))
+}
+
+/// adds a route, dropping the least recently registered of its kind. a token registered
+/// again moves to the back, endpoint routes are shared by the requests with the same query
+fn add_route(token: String, route: Route, kept: &Mutex<VecDeque<String>>, max: usize) {
+    let mut routes = ROUTES.lock().unwrap();
+    routes.insert(token.clone(), route);
+    let mut kept = kept.lock().unwrap();
+    kept.retain(|v| *v != token);
+    kept.push_back(token);
+    while kept.len() > max {
+        if let Some(v) = kept.pop_front() {
+            routes.remove(&v);
+        }
+    }
+}
+
+fn start_relay() -> Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").context("cant bind relay")?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+
+    // runs on its own thread, aleowrap blocks the rpc worker while it waits for us
+    std::thread::Builder::new()
+        .name("query-relay".to_string())
+        .spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    log::error!("cant create relay runtime: {}", e);
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                let make_svc =
+                    make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(handle)) });
+                let server = match hyper::Server::from_tcp(listener) {
+                    Ok(v) => v.serve(make_svc),
+                    Err(e) => {
+                        log::error!("cant start relay server: {}", e);
+                        return;
+                    }
+                };
+                if let Err(e) = server.await {
+                    log::error!("query relay stopped: {}", e);
+                }
+            });
+        })?;
+
+    log::info!("query relay listening on {}", addr);
+    Ok(addr)
+}
+
+async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|v| v.as_str().to_string())
+        .unwrap_or_default();
+    let (token, rest) = match path.trim_start_matches('/').split_once('/') {
+        Some((token, rest)) => (token.to_string(), format!("/{}", rest)),
+        None => return Ok(status_response(StatusCode::NOT_FOUND, "")),
+    };
+    let route = match ROUTES.lock().unwrap().get(&token) {
+        Some(v) => v.clone(),
+        None => return Ok(status_response(StatusCode::NOT_FOUND, "")),
+    };
+
+    let method = req.method().to_string();
+    let (endpoints, rest) = match route.upstream {
+        Upstream::Endpoints(endpoints, network) => (endpoints, with_network(&rest, &network)),
+        Upstream::Snapshot(snapshot) => {
+            return Ok(match snapshot.respond(&method, &rest) {
+                Some(v) => Response::new(Body::from(v)),
+                None => {
+                    log::warn!("{} {} is not in the state snapshot", method, rest);
+                    status_response(StatusCode::NOT_FOUND, "not in state snapshot")
+                }
+            });
+        }
+    };
+    let mut span = Span::start(
+        &format!("query {} {}", method, route_name(&rest)),
+        SpanKind::Client,
+        route.trace,
+    );
+    span.set_attribute("http.method", method.as_str());
+    span.set_attribute("http.target", rest.as_str());
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(v) => v.to_vec(),
+        Err(e) => return Ok(status_response(StatusCode::BAD_REQUEST, &e.to_string())),
+    };
+
+    let traceparent = span.context().map(|v| v.traceparent());
+    let result = tokio::task::spawn_blocking(move || {
+        if let Some(program_id) = program_path(&method, &rest) {
+            match programs::program(program_id, &endpoints) {
+                Ok(source) => {
+                    return Ok(HttpResponse {
+                        status: 200,
+                        body: serde_json::to_vec(&source)?,
+                    })
+                }
+                Err(e) => log::warn!("cant get {} from program cache: {:#}", program_id, e),
+            }
+        }
+        let body = if body.is_empty() { None } else { Some(body) };
+        let mut client = QueryClient::new(endpoints);
+        // the node sees the fetch as a child of this span
+        if let Some(traceparent) = traceparent {
+            client = client.with_header(format!("traceparent: {}", traceparent));
+        }
+        client.request(&method, &rest, body.as_deref())
+    })
+    .await;
+
+    match &result {
+        Ok(Ok(v)) => {
+            span.set_attribute("http.status_code", v.status);
+            if !v.is_success() {
+                span.set_error(format!("status {}", v.status));
+            }
+        }
+        Ok(Err(e)) => span.set_error(format!("{:#}", e)),
+        Err(e) => span.set_error(e),
+    }
+    span.end();
+
+    let response = match result {
+        Ok(Ok(v)) => Response::builder()
+            .status(StatusCode::from_u16(v.status as u16).unwrap_or(StatusCode::BAD_GATEWAY))
+            .body(Body::from(v.body))
+            .unwrap(),
+        Ok(Err(e)) => status_response(StatusCode::BAD_GATEWAY, &format!("{:#}", e)),
+        Err(e) => status_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    Ok(response)
+}
+
+/// `path` with its network segment replaced by `network`, `/mainnet` for example
+fn with_network(path: &str, network: &str) -> String {
+    match path.strip_prefix('/').and_then(|v| v.split_once('/')) {
+        Some((_, rest)) => format!("{}/{}", network, rest),
+        None => path.to_string(),
+    }
+}
+
+/// program id of a `GET /<network>/program/<id>` request, answered from the program cache
+fn program_path<'a>(method: &str, path: &'a str) -> Option<&'a str> {
+    if method != "GET" {
+        return None;
+    }
+    let (_, rest) = path.strip_prefix('/')?.split_once('/')?;
+    rest.strip_prefix("program/")
+        .filter(|v| !v.is_empty() && !v.contains(['/', '?']))
+}
+
+/// path without the values in it, `/testnet3/program/credits.aleo` is `/testnet3/program`
+fn route_name(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    path.split('/')
+        .filter(|v| !v.is_empty())
+        .take(2)
+        .fold(String::new(), |name, v| format!("{}/{}", name, v))
+}
+
+fn status_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::HttpClient;
+
+    #[test]
+    fn test_snapshot_route() {
+        let snapshot = StateSnapshot {
+            state_root: "ar1state".to_string(),
+            ..Default::default()
+        };
+        let url = snapshot_url(snapshot).unwrap();
+        let client = HttpClient::with_proxy(None);
+
+        let response = client
+            .get(&format!("{}/testnet3/latest/stateRoot", url))
+            .unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.body, b"\"ar1state\"");
+
+        let response = client
+            .get(&format!("{}/testnet3/program/credits.aleo", url))
+            .unwrap();
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn test_add_route() {
+        let kept = Mutex::new(VecDeque::new());
+        let route = || Route {
+            upstream: Upstream::Endpoints(
+                vec!["http://127.0.0.1:3030".to_string()],
+                "/testnet3".to_string(),
+            ),
+            trace: None,
+        };
+        add_route("route-a".to_string(), route(), &kept, 2);
+        add_route("route-b".to_string(), route(), &kept, 2);
+        add_route("route-a".to_string(), route(), &kept, 2);
+        add_route("route-c".to_string(), route(), &kept, 2);
+        let routes = ROUTES.lock().unwrap();
+        assert!(routes.contains_key("route-a"));
+        assert!(!routes.contains_key("route-b"));
+        assert!(routes.contains_key("route-c"));
+        assert_eq!(kept.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_program_path() {
+        assert_eq!(
+            program_path("GET", "/testnet3/program/credits.aleo"),
+            Some("credits.aleo")
+        );
+        assert_eq!(
+            program_path("GET", "/testnet3/program/credits.aleo/mappings"),
+            None
+        );
+        assert_eq!(program_path("POST", "/testnet3/program/credits.aleo"), None);
+        assert_eq!(program_path("GET", "/testnet3/latest/height"), None);
+        assert_eq!(
+            program_path("GET", "/mainnet/program/credits.aleo"),
+            Some("credits.aleo")
+        );
+    }
+
+    #[test]
+    fn test_with_network() {
+        assert_eq!(
+            with_network("/testnet3/latest/stateRoot", "/mainnet"),
+            "/mainnet/latest/stateRoot"
+        );
+        assert_eq!(
+            with_network("/testnet3/blocks?start=1&end=2", "/testnet3"),
+            "/testnet3/blocks?start=1&end=2"
+        );
+        assert_eq!(with_network("/stateRoot", "/mainnet"), "/stateRoot");
+    }
+}
@@ -0,0 +1,111 @@
+//! Ledger state handed in by the caller, for proving without a node.
+//!
+//! The relay answers aleowrap's queries from the snapshot instead of a node,
+//! anything not in it is a 404 so a missing entry fails the request instead of
+//! reaching out. snarkvm's parameters aren't part of it, they are read from the
+//! local parameters dir and a request fails before proving when they are missing,
+//! see `aleo::parameters`.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// `ar1...` root the state paths were built against
+    pub state_root: String,
+    /// state path of each input record, by record commitment
+    #[serde(default)]
+    pub state_paths: HashMap<String, String>,
+    /// source of each program called or imported, by program id
+    #[serde(default)]
+    pub programs: HashMap<String, String>,
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+impl StateSnapshot {
+    pub fn validate(&self) -> Result<()> {
+        if self.state_root.is_empty() {
+            return Err(anyhow!("state snapshot has no state root"));
+        }
+        if let Some((commitment, _)) = self.state_paths.iter().find(|v| v.1.is_empty()) {
+            return Err(anyhow!("state path of {} is empty", commitment));
+        }
+        if let Some((id, _)) = self.programs.iter().find(|v| v.1.is_empty()) {
+            return Err(anyhow!("source of {} is empty", id));
+        }
+        Ok(())
+    }
+
+    /// body a node would return for `path`, none when the snapshot doesn't have it
+    pub fn respond(&self, method: &str, path: &str) -> Option<Vec<u8>> {
+        if method != "GET" {
+            return None;
+        }
+        let path = path.split('?').next().unwrap_or_default();
+        // the first segment is the network, `/testnet3/latest/stateRoot`
+        let segments: Vec<&str> = path.split('/').filter(|v| !v.is_empty()).collect();
+        let value = match segments.as_slice() {
+            [_, "latest", "stateRoot"] => serde_json::to_vec(&self.state_root),
+            [_, "latest", "height"] => serde_json::to_vec(&self.height?),
+            [_, "statePath", commitment] => serde_json::to_vec(self.state_paths.get(*commitment)?),
+            [_, "program", id] => serde_json::to_vec(self.programs.get(*id)?),
+            _ => return None,
+        };
+        value.ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot() -> StateSnapshot {
+        StateSnapshot {
+            state_root: "ar1state".to_string(),
+            state_paths: HashMap::from([("1234field".to_string(), "path1".to_string())]),
+            programs: HashMap::from([(
+                "hello.aleo".to_string(),
+                "program hello.aleo;".to_string(),
+            )]),
+            height: None,
+        }
+    }
+
+    #[test]
+    fn test_respond() {
+        let snapshot = snapshot();
+        assert_eq!(
+            snapshot.respond("GET", "/testnet3/latest/stateRoot"),
+            Some(b"\"ar1state\"".to_vec())
+        );
+        assert_eq!(
+            snapshot.respond("GET", "/testnet3/statePath/1234field"),
+            Some(b"\"path1\"".to_vec())
+        );
+        assert_eq!(
+            snapshot.respond("GET", "/testnet3/program/hello.aleo"),
+            Some(b"\"program hello.aleo;\"".to_vec())
+        );
+        assert!(snapshot
+            .respond("GET", "/testnet3/statePath/5678field")
+            .is_none());
+        assert!(snapshot.respond("GET", "/testnet3/latest/height").is_none());
+        assert!(snapshot
+            .respond("POST", "/testnet3/transaction/broadcast")
+            .is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(snapshot().validate().is_ok());
+        assert!(StateSnapshot::default().validate().is_err());
+        let mut snapshot = snapshot();
+        snapshot
+            .programs
+            .insert("empty.aleo".to_string(), String::new());
+        assert!(snapshot.validate().is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! Broadcasting transactions and following them until a node confirms them.
+//!
+//! Every broadcast transaction is kept in the config db under `tx:<id>` and
+//! checked in the background with a growing delay, so `transaction_status`
+//! usually answers from the db.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use lazy_static::lazy_static;
+use rocksdb::DB;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{retry, QueryClient};
+use crate::config::Config;
+
+const TX_KEY_PREFIX: &str = "tx:";
+const BROADCAST_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+// pending transactions are checked until this long after tracking started
+const TRACK_FOR: Duration = Duration::from_secs(30 * 60);
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(5);
+const MAX_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref TRACKED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl TransactionStatus {
+    pub fn is_final(&self) -> bool {
+        *self != Self::Pending
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub id: String,
+    pub status: TransactionStatus,
+    /// endpoints the status is checked against
+    pub endpoints: Vec<String>,
+    /// none for transactions broadcast by someone else
+    pub broadcast_at: Option<String>,
+    pub updated_at: String,
+    /// error of the last status check, cleared by the next successful one
+    pub error: Option<String>,
+}
+
+/// transactions kept in the config db
+pub struct TransactionStore {
+    db: Arc<DB>,
+}
+
+impl TransactionStore {
+    pub fn open() -> Result<Self> {
+        let db = Config::get_config().db.context("cant get db")?;
+        Ok(Self { db })
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<TransactionRecord>> {
+        match self.db.get(record_key(id)).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode transaction record"),
+            None => Ok(None),
+        }
+    }
+
+    pub fn insert(&self, record: &TransactionRecord) -> Result<()> {
+        self.db
+            .put(record_key(&record.id), serde_json::to_vec(record)?)
+            .context("cant write to db")
+    }
+}
+
+fn record_key(id: &str) -> String {
+    format!("{}{}", TX_KEY_PREFIX, id)
+}
+
+/// sends `transaction` to the query endpoints and starts tracking it
+pub fn broadcast(transaction: &str, query: Option<&str>) -> Result<TransactionRecord> {
+    let id = transaction_id(transaction)?;
+    let config = Config::get_config();
+    let endpoints = config.query_endpoints(query);
+    let path = config.network_path("/transaction/broadcast");
+    let client = QueryClient::new(endpoints.clone());
+    // errors are unreachable or failing endpoints, a 4xx is the node refusing the transaction
+    let response = retry(BROADCAST_ATTEMPTS, RETRY_DELAY, || {
+        client.request("POST", &path, Some(transaction.as_bytes()))
+    })?;
+    // a retry after a lost response is refused by the node that took the first attempt
+    if !response.is_success() && !is_duplicate(&response.body) {
+        return Err(anyhow!(
+            "broadcast failed with status {}: {}",
+            response.status,
+            String::from_utf8_lossy(&response.body)
+        ));
+    }
+
+    let now = Local::now().to_rfc3339();
+    let record = TransactionRecord {
+        id,
+        status: TransactionStatus::Pending,
+        endpoints,
+        broadcast_at: Some(now.clone()),
+        updated_at: now,
+        error: None,
+    };
+    TransactionStore::open()?.insert(&record)?;
+    log::info!("broadcast transaction {}", record.id);
+    track(record.id.clone());
+    Ok(record)
+}
+
+/// whether a node refused a broadcast because it already has the transaction
+fn is_duplicate(body: &[u8]) -> bool {
+    String::from_utf8_lossy(body).contains("already exists")
+}
+
+/// status of a transaction, checked against the node unless it is already final.
+/// transactions the service didn't broadcast are checked once, neither saved nor tracked
+pub fn transaction_status(id: &str, query: Option<&str>) -> Result<TransactionRecord> {
+    if !id.starts_with("at1") {
+        return Err(anyhow!("invalid transaction id {}", id));
+    }
+    let store = TransactionStore::open()?;
+    let mut record = match store.get(id)? {
+        Some(v) if v.status.is_final() => return Ok(v),
+        Some(v) => v,
+        None => {
+            let mut record = TransactionRecord {
+                id: id.to_string(),
+                status: TransactionStatus::Pending,
+                endpoints: Config::get_config().query_endpoints(query),
+                broadcast_at: None,
+                updated_at: Local::now().to_rfc3339(),
+                error: None,
+            };
+            check(&mut record);
+            return Ok(record);
+        }
+    };
+    if query.is_some() || record.endpoints.is_empty() {
+        record.endpoints = Config::get_config().query_endpoints(query);
+    }
+    check(&mut record);
+    store.insert(&record)?;
+    if !record.status.is_final() {
+        track(record.id.clone());
+    }
+    Ok(record)
+}
+
+/// checks a broadcast transaction until it is final or `timeout` passed, with the
+/// tracker's delays. the status as last checked
+pub fn wait_final(id: &str, timeout: Duration) -> Result<TransactionRecord> {
+    let started = Instant::now();
+    let mut delay = FIRST_CHECK_DELAY;
+    loop {
+        let record = transaction_status(id, None)?;
+        let left = timeout.saturating_sub(started.elapsed());
+        if record.status.is_final() || left.is_zero() {
+            return Ok(record);
+        }
+        thread::sleep(delay.min(left));
+        delay = (delay * 2).min(MAX_CHECK_DELAY);
+    }
+}
+
+fn transaction_id(transaction: &str) -> Result<String> {
+    let transaction: Value = serde_json::from_str(transaction).context("invalid transaction")?;
+    transaction
+        .get("id")
+        .and_then(|v| v.as_str())
+        .filter(|v| v.starts_with("at1"))
+        .map(|v| v.to_string())
+        .context("transaction has no id")
+}
+
+fn check(record: &mut TransactionRecord) {
+    match fetch_status(&record.id, &record.endpoints) {
+        Ok(status) => {
+            record.status = status;
+            record.error = None;
+        }
+        Err(e) => record.error = Some(format!("{:#}", e)),
+    }
+    record.updated_at = Local::now().to_rfc3339();
+}
+
+fn fetch_status(id: &str, endpoints: &[String]) -> Result<TransactionStatus> {
+    let config = Config::get_config();
+    let client = QueryClient::new(endpoints.to_vec());
+    let path = config.network_path(&format!("/transaction/confirmed/{}", id));
+    let response = client.request("GET", &path, None)?;
+    if response.is_success() {
+        let confirmed: Value =
+            serde_json::from_slice(&response.body).context("cant decode confirmed transaction")?;
+        return Ok(confirmed_status(&confirmed));
+    }
+    // nodes without the confirmed route only return transactions that made it into a block
+    let path = config.network_path(&format!("/transaction/{}", id));
+    let response = client.request("GET", &path, None)?;
+    match response.is_success() {
+        true => Ok(TransactionStatus::Accepted),
+        false => Ok(TransactionStatus::Pending),
+    }
+}
+
+/// a confirmed transaction's `type` is `accepted_execute`, `rejected_deploy` and so on
+fn confirmed_status(confirmed: &Value) -> TransactionStatus {
+    let kind = confirmed
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    match kind.starts_with("rejected") {
+        true => TransactionStatus::Rejected,
+        false => TransactionStatus::Accepted,
+    }
+}
+
+/// checks `id` in the background until it is final, once per transaction
+fn track(id: String) {
+    if !TRACKED.lock().unwrap().insert(id.clone()) {
+        return;
+    }
+    let tracked = id.clone();
+    let result = thread::Builder::new()
+        .name("tx-tracker".to_string())
+        .spawn(move || {
+            follow(&tracked);
+            TRACKED.lock().unwrap().remove(&tracked);
+        });
+    if let Err(e) = result {
+        log::error!("cant track transaction {}: {}", id, e);
+        TRACKED.lock().unwrap().remove(&id);
+    }
+}
+
+fn follow(id: &str) {
+    let started = Instant::now();
+    let mut delay = FIRST_CHECK_DELAY;
+    while started.elapsed() < TRACK_FOR {
+        thread::sleep(delay);
+        delay = (delay * 2).min(MAX_CHECK_DELAY);
+        let result = TransactionStore::open().and_then(|store| {
+            let mut record = store.get(id)?.context("transaction record is gone")?;
+            if !record.status.is_final() {
+                check(&mut record);
+                store.insert(&record)?;
+            }
+            Ok(record.status)
+        });
+        match result {
+            Ok(status) if status.is_final() => {
+                log::info!("transaction {} is {:?}", id, status);
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("cant track transaction {}: {:#}", id, e);
+                return;
+            }
+        }
+    }
+    log::warn!(
+        "transaction {} still pending after {} minutes",
+        id,
+        TRACK_FOR.as_secs() / 60
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_id() {
+        let transaction = r#"{"type":"execute","id":"at1abc","execution":{}}"#;
+        assert_eq!(transaction_id(transaction).unwrap(), "at1abc");
+        assert!(transaction_id(r#"{"type":"execute"}"#).is_err());
+        assert!(transaction_id("at1abc").is_err());
+    }
+
+    #[test]
+    fn test_is_duplicate() {
+        assert!(is_duplicate(
+            b"Transaction 'at1abc' already exists in the ledger"
+        ));
+        assert!(!is_duplicate(b"Invalid transaction"));
+    }
+
+    #[test]
+    fn test_confirmed_status() {
+        assert_eq!(
+            confirmed_status(&json!({ "type": "accepted_execute", "index": 0 })),
+            TransactionStatus::Accepted
+        );
+        assert_eq!(
+            confirmed_status(&json!({ "type": "rejected_execute", "index": 1 })),
+            TransactionStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = std::env::temp_dir().join(format!("aleo-acc-tx-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = TransactionStore {
+            db: Arc::new(DB::open_default(dir).unwrap()),
+        };
+        let record = TransactionRecord {
+            id: "at1store".to_string(),
+            status: TransactionStatus::Pending,
+            endpoints: vec!["http://127.0.0.1:3030".to_string()],
+            broadcast_at: None,
+            updated_at: Local::now().to_rfc3339(),
+            error: None,
+        };
+        store.insert(&record).unwrap();
+        assert_eq!(store.get("at1store").unwrap(), Some(record));
+        assert!(store.get("at1missing").unwrap().is_none());
+    }
+}
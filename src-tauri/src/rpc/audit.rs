@@ -0,0 +1,917 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Local};
+use jsonrpc_core::{Call, Output, Params};
+use rocksdb::{Direction, IteratorMode, DB};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{config::Config, logger::redact::redact_error};
+
+use super::context::RequestContext;
+
+const AUDIT_KEY_PREFIX: &str = "audit:";
+// first key after every `audit:` key
+const AUDIT_KEY_END: &str = "audit;";
+const AUDIT_RETENTION_KEY: &str = "audit_retention";
+// the retention policy is applied every this many records
+const PRUNE_INTERVAL: u64 = 100;
+
+/// parameter names of each rpc method in call order, clients send them positionally
+const PARAM_NAMES: &[(&str, &[&str])] = &[
+    (
+        "deploy",
+        &[
+            "private_key",
+            "program",
+            "fee_record",
+            "imports",
+            "priority_fee_in_microcredits",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "execute",
+        &[
+            "private_key",
+            "program_id",
+            "function",
+            "inputs",
+            "record",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "transfer",
+        &[
+            "private_key",
+            "recipient",
+            "amount",
+            "function",
+            "input_record",
+            "fee_record",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "join",
+        &[
+            "private_key",
+            "first_record",
+            "second_record",
+            "fee_record",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "split",
+        &["private_key", "record", "amount", "query", "snapshot"],
+    ),
+    ("deployment_cost", &["program", "imports"]),
+    (
+        "execution_costv2",
+        &["program_id", "function", "inputs", "query", "snapshot"],
+    ),
+    ("deployment_costv2", &["program", "imports", "fee"]),
+    (
+        "execution_costv3",
+        &[
+            "program_id",
+            "function",
+            "inputs",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    ("decrypt_recordsv2", &["view_key", "records"]),
+    (
+        "transaction_from_authorization",
+        &[
+            "program_id",
+            "execute_authorization_str",
+            "fee_authorization_str",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "deploy_from_authorization",
+        &[
+            "program",
+            "imports",
+            "owner_str",
+            "fee_authorization_str",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "deployv2",
+        &[
+            "private_key",
+            "program",
+            "imports",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "executev2",
+        &[
+            "private_key",
+            "program_id",
+            "function",
+            "inputs",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "transferv2",
+        &[
+            "private_key",
+            "recipient",
+            "amount",
+            "function",
+            "input_record",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "joinv2",
+        &[
+            "private_key",
+            "first_record",
+            "second_record",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "splitv2",
+        &[
+            "private_key",
+            "record",
+            "amount",
+            "fee",
+            "query",
+            "snapshot",
+        ],
+    ),
+    (
+        "authorize_execute",
+        &["private_key", "program_id", "function", "inputs", "query"],
+    ),
+    (
+        "authorize_fee",
+        &[
+            "private_key",
+            "execute_authorization_str",
+            "base_fee_in_microcredits",
+            "fee",
+            "query",
+        ],
+    ),
+    (
+        "authorize_deploy_fee",
+        &[
+            "private_key",
+            "program",
+            "imports",
+            "base_fee_in_microcredits",
+            "fee",
+            "query",
+        ],
+    ),
+    ("broadcast", &["transaction", "query"]),
+    (
+        "execute_and_broadcast",
+        &[
+            "private_key",
+            "program_id",
+            "function",
+            "inputs",
+            "fee",
+            "query",
+        ],
+    ),
+    ("transaction_status", &["transaction_id", "query"]),
+    ("verify_transaction", &["transaction", "query"]),
+    (
+        "execute_bundle",
+        &["private_key", "steps", "broadcast", "query"],
+    ),
+    (
+        "scan_records",
+        &["view_key", "start_height", "end_height", "query"],
+    ),
+    ("generate_account", &[]),
+    ("derive_account", &["private_key"]),
+    ("sign", &["private_key", "message"]),
+    ("verify", &["address", "message", "signature"]),
+    ("discovery", &[]),
+    ("update", &["version"]),
+    ("stats", &[]),
+];
+
+/// the only parameters written to the audit trail, anything else may hold keys or records
+const AUDITED_PARAMS: &[&str] = &[
+    "recipient",
+    "amount",
+    "program_id",
+    "function",
+    "fee",
+    "priority_fee_in_microcredits",
+    "base_fee_in_microcredits",
+    "version",
+    "transaction_id",
+    "start_height",
+    "end_height",
+    "address",
+    "broadcast",
+];
+
+static RECORDED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub id: String,
+    pub timestamp: String,
+    /// fingerprint of the client's public key
+    pub client: Option<String>,
+    pub method: Option<String>,
+    pub params: BTreeMap<String, Value>,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub transaction_id: Option<String>,
+    /// every transaction an `execute_bundle` call built, in step order
+    #[serde(default)]
+    pub transaction_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRetention {
+    pub max_records: usize,
+    pub max_age_days: u64,
+}
+
+impl Default for AuditRetention {
+    fn default() -> Self {
+        Self {
+            max_records: 10000,
+            max_age_days: 90,
+        }
+    }
+}
+
+impl AuditRetention {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_records == 0 || self.max_age_days == 0 {
+            return Err(anyhow!("audit retention must keep at least one record"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub method: Option<String>,
+    pub client: Option<String>,
+    /// rfc3339 time, inclusive
+    pub from: Option<String>,
+    /// rfc3339 time, exclusive
+    pub to: Option<String>,
+    pub success: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        if self.method.is_some() && self.method != record.method {
+            return false;
+        }
+        if self.client.is_some() && self.client != record.client {
+            return false;
+        }
+        if self.success.is_some() && self.success != Some(record.success) {
+            return false;
+        }
+        true
+    }
+}
+
+/// rpc calls kept in the config db under `audit:<millis>:<request id>`, so keys
+/// sort by time
+pub struct AuditLog {
+    db: Arc<DB>,
+}
+
+impl AuditLog {
+    pub fn open() -> Result<Self> {
+        let db = Config::get_config().db.context("cant get db")?;
+        Ok(Self { db })
+    }
+
+    pub fn insert(&self, record: &AuditRecord) -> Result<()> {
+        let time = DateTime::parse_from_rfc3339(&record.timestamp)?;
+        self.db
+            .put(
+                record_key(time.timestamp_millis(), &record.id),
+                serde_json::to_vec(record)?,
+            )
+            .context("cant write to db")?;
+
+        if RECORDED.fetch_add(1, Ordering::Relaxed) % PRUNE_INTERVAL == 0 {
+            self.prune(&self.get_retention()?)?;
+        }
+        Ok(())
+    }
+
+    /// matching records, newest first
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>> {
+        let from = parse_time(query.from.as_deref())?;
+        let to = parse_time(query.to.as_deref())?;
+        let start = match to {
+            Some(v) => record_key(v, ""),
+            None => AUDIT_KEY_END.to_string(),
+        };
+
+        let mut records = Vec::new();
+        let iter = self
+            .db
+            .iterator(IteratorMode::From(start.as_bytes(), Direction::Reverse));
+        for item in iter {
+            let (key, value) = item.context("cant read db")?;
+            let time = match key_millis(&key) {
+                Some(v) => v,
+                None => break,
+            };
+            if to.map(|v| time >= v).unwrap_or(false) {
+                continue;
+            }
+            if from.map(|v| time < v).unwrap_or(false) {
+                break;
+            }
+            let record: AuditRecord =
+                serde_json::from_slice(&value).context("cant decode audit record")?;
+            if query.matches(&record) {
+                records.push(record);
+                if query.limit.map(|v| records.len() >= v).unwrap_or(false) {
+                    break;
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.delete_range(AUDIT_KEY_PREFIX, AUDIT_KEY_END)
+    }
+
+    pub fn get_retention(&self) -> Result<AuditRetention> {
+        match self.db.get(AUDIT_RETENTION_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode audit retention"),
+            None => Ok(AuditRetention::default()),
+        }
+    }
+
+    pub fn set_retention(&self, retention: &AuditRetention) -> Result<()> {
+        retention.validate()?;
+        self.db
+            .put(AUDIT_RETENTION_KEY, serde_json::to_vec(retention)?)
+            .context("cant write to db")?;
+        self.prune(retention)
+    }
+
+    /// deletes records older than the retention age, then the oldest beyond the count limit
+    fn prune(&self, retention: &AuditRetention) -> Result<()> {
+        let max_age = Duration::days(retention.max_age_days as i64);
+        let oldest = record_key((Local::now() - max_age).timestamp_millis(), "");
+        self.delete_range(AUDIT_KEY_PREFIX, &oldest)?;
+
+        let iter = self.db.iterator(IteratorMode::From(
+            AUDIT_KEY_END.as_bytes(),
+            Direction::Reverse,
+        ));
+        for (index, item) in iter.enumerate() {
+            let (key, _) = item.context("cant read db")?;
+            if key_millis(&key).is_none() {
+                break;
+            }
+            if index >= retention.max_records {
+                self.db.delete(&key).context("cant delete from db")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_range(&self, from: &str, to: &str) -> Result<()> {
+        let iter = self
+            .db
+            .iterator(IteratorMode::From(from.as_bytes(), Direction::Forward));
+        for item in iter {
+            let (key, _) = item.context("cant read db")?;
+            if key.as_ref() >= to.as_bytes() {
+                break;
+            }
+            self.db.delete(&key).context("cant delete from db")?;
+        }
+        Ok(())
+    }
+}
+
+fn record_key(millis: i64, id: &str) -> String {
+    format!("{}{:020}:{}", AUDIT_KEY_PREFIX, millis.max(0), id)
+}
+
+fn key_millis(key: &[u8]) -> Option<i64> {
+    let key = std::str::from_utf8(key).ok()?;
+    let rest = key.strip_prefix(AUDIT_KEY_PREFIX)?;
+    rest.split(':').next()?.parse().ok()
+}
+
+fn parse_time(time: Option<&str>) -> Result<Option<i64>> {
+    time.map(|v| {
+        DateTime::parse_from_rfc3339(v)
+            .map(|v| v.timestamp_millis())
+            .context(format!("invalid time {}", v))
+    })
+    .transpose()
+}
+
+/// the audited parameters of a call, read before it is handled
+pub fn request_params(call: &Call) -> BTreeMap<String, Value> {
+    let call = match call {
+        Call::MethodCall(v) => v,
+        _ => return BTreeMap::new(),
+    };
+    let names = PARAM_NAMES
+        .iter()
+        .find(|v| v.0 == call.method)
+        .map(|v| v.1)
+        .unwrap_or_default();
+
+    let named: Vec<(&str, &Value)> = match &call.params {
+        Params::Array(values) => names.iter().copied().zip(values.iter()).collect(),
+        Params::Map(map) => map.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+        Params::None => Vec::new(),
+    };
+
+    let mut params = BTreeMap::new();
+    for (name, value) in named {
+        if value.is_null() {
+            continue;
+        }
+        if name == "fee" && value.is_object() {
+            params.insert(name.to_string(), audited_fee(value));
+        } else if name == "steps" {
+            if let Some(steps) = value.as_array() {
+                let steps = steps.iter().map(audited_step).collect();
+                params.insert(name.to_string(), Value::Array(steps));
+            }
+        } else if AUDITED_PARAMS.contains(&name) {
+            params.insert(name.to_string(), value.clone());
+        } else if name == "program" {
+            // only the id of a deployed program is kept, not its source
+            if let Some(id) = value.as_str().and_then(program_id) {
+                params.insert("program_id".to_string(), Value::String(id));
+            }
+        }
+    }
+    params
+}
+
+/// the unified fee may carry the fee record
+fn audited_fee(fee: &Value) -> Value {
+    let mut fee = fee.clone();
+    if let Some(fee) = fee.as_object_mut() {
+        fee.remove("fee_record");
+    }
+    fee
+}
+
+/// a step of `execute_bundle` with its method and audited parameters, its records and
+/// inputs are left out like those of the method it stands for
+fn audited_step(step: &Value) -> Value {
+    let step = match step.as_object() {
+        Some(v) => v,
+        None => return Value::Null,
+    };
+    let mut audited = serde_json::Map::new();
+    for (name, value) in step {
+        if value.is_null() {
+            continue;
+        }
+        if name == "fee" {
+            audited.insert(name.clone(), audited_fee(value));
+        } else if name == "method" || AUDITED_PARAMS.contains(&name.as_str()) {
+            audited.insert(name.clone(), value.clone());
+        }
+    }
+    Value::Object(audited)
+}
+
+fn program_id(program: &str) -> Option<String> {
+    program
+        .lines()
+        .map(|v| v.trim())
+        .find_map(|v| v.strip_prefix("program "))
+        .map(|v| v.trim_end_matches(';').trim().to_string())
+}
+
+/// whether the call succeeded, its error and the result it returned
+fn outcome(output: Option<&Output>) -> (bool, Option<String>, Option<&Value>) {
+    match output {
+        Some(Output::Success(v)) => (true, None, Some(&v.result)),
+        Some(Output::Failure(v)) => (false, Some(redact_error(&v.error.message)), None),
+        // notifications get no answer
+        None => (true, None, None),
+    }
+}
+
+/// ids of the transactions of a bundle result, empty for anything else
+fn transaction_ids(result: &Value) -> Vec<String> {
+    result
+        .get("transactions")
+        .and_then(|v| v.as_array())
+        .map(|transactions| {
+            transactions
+                .iter()
+                .filter_map(|v| v.get("transaction").and_then(transaction_id))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// rpc methods return transactions as json strings, their id starts with `at1`.
+/// broadcast methods return the tracked transaction instead
+fn transaction_id(result: &Value) -> Option<String> {
+    let id = match result {
+        Value::Object(_) => result.get("id")?.as_str()?.to_string(),
+        v => {
+            let transaction: Value = serde_json::from_str(v.as_str()?).ok()?;
+            transaction.get("id")?.as_str()?.to_string()
+        }
+    };
+    id.starts_with("at1").then_some(id)
+}
+
+pub fn record(context: &RequestContext, params: BTreeMap<String, Value>, output: Option<&Output>) {
+    let elapsed = context.started.elapsed();
+    let started_at =
+        Local::now() - Duration::from_std(elapsed).unwrap_or_else(|_| Duration::zero());
+    let (success, error, result) = outcome(output);
+    let record = AuditRecord {
+        id: context.id.clone(),
+        timestamp: started_at.to_rfc3339(),
+        client: context.fingerprint.clone(),
+        method: context.method.clone(),
+        params,
+        duration_ms: elapsed.as_millis() as u64,
+        success,
+        error,
+        transaction_id: result.and_then(transaction_id),
+        transaction_ids: result.map(transaction_ids).unwrap_or_default(),
+    };
+
+    if let Err(e) = AuditLog::open().and_then(|v| v.insert(&record)) {
+        log::error!(target: "rpc", "cant write audit record: {:#}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_audit_records(query: Option<AuditQuery>) -> Result<Vec<AuditRecord>, String> {
+    AuditLog::open()
+        .and_then(|v| v.query(&query.unwrap_or_default()))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn clear_audit_records() -> Result<(), String> {
+    AuditLog::open()
+        .and_then(|v| v.clear())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_audit_retention() -> Result<AuditRetention, String> {
+    AuditLog::open()
+        .and_then(|v| v.get_retention())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_audit_retention(retention: AuditRetention) -> Result<(), String> {
+    AuditLog::open()
+        .and_then(|v| v.set_retention(&retention))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "APrivateKey1zkp8CZNn3yeCseEtxuVPbDCwSyhGW6yZKUYKfgXmcpoGPWH";
+
+    fn request(method: &str, params: Params) -> Call {
+        Call::MethodCall(jsonrpc_core::MethodCall {
+            jsonrpc: Some(jsonrpc_core::Version::V2),
+            method: method.to_string(),
+            params,
+            id: jsonrpc_core::Id::Num(1),
+        })
+    }
+
+    /// the parameters of each `#[rpc]` method in order, read from the trait's source
+    fn rpc_signatures() -> Vec<(String, Vec<String>)> {
+        let source = include_str!("rpc.rs");
+        let source = &source[..source.find("impl Rpc for RpcImpl").unwrap()];
+        source
+            .split("#[rpc(name = \"")
+            .skip(1)
+            .map(|v| {
+                let (method, rest) = v.split_once('"').unwrap();
+                let start = rest.find("&self").unwrap() + "&self".len();
+                let end = rest.find(") ->").unwrap();
+                // commas inside generics like HashMap<String, String> don't split
+                let mut depth = 0;
+                let mut params = vec![String::new()];
+                for c in rest[start..end].chars() {
+                    match c {
+                        '<' => depth += 1,
+                        '>' => depth -= 1,
+                        ',' if depth == 0 => {
+                            params.push(String::new());
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    params.last_mut().unwrap().push(c);
+                }
+                let params = params
+                    .iter()
+                    .filter_map(|v| v.split_once(':'))
+                    .map(|v| v.0.trim().to_string())
+                    .collect();
+                (method.to_string(), params)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_param_names_match_rpc() {
+        let signatures = rpc_signatures();
+        assert_eq!(PARAM_NAMES.len(), signatures.len());
+        for (method, params) in signatures.iter() {
+            let names = PARAM_NAMES
+                .iter()
+                .find(|v| v.0 == method)
+                .unwrap_or_else(|| panic!("{} has no parameter names", method))
+                .1;
+            assert_eq!(
+                names.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+                *params,
+                "parameters of {}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn test_params_never_keep_keys() {
+        let params = request_params(&request(
+            "transfer",
+            Params::Array(vec![
+                Value::from(PRIVATE_KEY),
+                Value::from("aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px"),
+                Value::from(100),
+                Value::from("public"),
+                Value::Null,
+                Value::from("{ owner: aleo1.private, _nonce: 1group.public }"),
+                Value::from(3000),
+            ]),
+        ));
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            ["amount", "fee", "function", "recipient"]
+        );
+        assert!(!serde_json::to_string(&params)
+            .unwrap()
+            .contains("APrivateKey1"));
+    }
+
+    #[test]
+    fn test_fee_record_not_kept() {
+        let params = request_params(&request(
+            "transferv2",
+            Params::Array(vec![
+                Value::from(PRIVATE_KEY),
+                Value::from("aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px"),
+                Value::from(100),
+                Value::from("public"),
+                Value::Null,
+                serde_json::json!({
+                    "priority_fee_in_microcredits": 3000,
+                    "payment": "private",
+                    "fee_record": "{ owner: aleo1.private, _nonce: 1group.public }",
+                }),
+            ]),
+        ));
+        assert_eq!(
+            params["fee"],
+            serde_json::json!({ "priority_fee_in_microcredits": 3000, "payment": "private" })
+        );
+    }
+
+    #[test]
+    fn test_bundle_steps() {
+        let params = request_params(&request(
+            "execute_bundle",
+            Params::Array(vec![
+                Value::from(PRIVATE_KEY),
+                serde_json::json!([
+                    { "method": "split", "record": "{ owner: aleo1.private }", "amount": 100 },
+                    {
+                        "method": "transfer",
+                        "recipient": "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px",
+                        "amount": 100,
+                        "function": "private",
+                        "input_record": "$0.0",
+                        "fee": { "payment": "private", "fee_record": "$0.1" }
+                    },
+                    {
+                        "method": "execute",
+                        "program_id": "hello.aleo",
+                        "function": "main",
+                        "inputs": ["1u32"],
+                        "fee": null
+                    }
+                ]),
+                Value::from(true),
+            ]),
+        ));
+        assert_eq!(
+            params["steps"],
+            serde_json::json!([
+                { "method": "split", "amount": 100 },
+                {
+                    "method": "transfer",
+                    "recipient": "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px",
+                    "amount": 100,
+                    "function": "private",
+                    "fee": { "payment": "private" }
+                },
+                { "method": "execute", "program_id": "hello.aleo", "function": "main" }
+            ])
+        );
+        assert_eq!(params["broadcast"], true);
+    }
+
+    #[test]
+    fn test_deploy_program_id() {
+        let program = "import credits.aleo;\n\nprogram hello_world.aleo;\n\nfunction main:\n";
+        let params = request_params(&request(
+            "deploy",
+            Params::Array(vec![Value::from(PRIVATE_KEY), Value::from(program)]),
+        ));
+        assert_eq!(params["program_id"], "hello_world.aleo");
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn test_transaction_id() {
+        let transaction = Value::from(
+            r#"{"type":"execute","id":"at1qz4wlmgq0rupxhmsjsygykcgzmvfu4yz0gzfkvr2e9kpkxd4h5rs2cndj0"}"#,
+        );
+        assert_eq!(
+            transaction_id(&transaction).as_deref(),
+            Some("at1qz4wlmgq0rupxhmsjsygykcgzmvfu4yz0gzfkvr2e9kpkxd4h5rs2cndj0")
+        );
+        assert_eq!(transaction_id(&Value::from("1000")), None);
+        let bundle = serde_json::json!({
+            "transactions": [
+                { "transaction": transaction, "records": [], "broadcast": null },
+                { "transaction": r#"{"id":"at1second"}"#, "records": [], "broadcast": null }
+            ],
+            "remaining": []
+        });
+        assert_eq!(transaction_id(&bundle), None);
+        assert_eq!(
+            transaction_ids(&bundle),
+            [
+                "at1qz4wlmgq0rupxhmsjsygykcgzmvfu4yz0gzfkvr2e9kpkxd4h5rs2cndj0",
+                "at1second"
+            ]
+        );
+        assert_eq!(
+            transaction_id(&serde_json::json!({ "id": "at1tracked", "status": "pending" }))
+                .as_deref(),
+            Some("at1tracked")
+        );
+    }
+
+    fn temp_log(name: &str) -> AuditLog {
+        let dir =
+            std::env::temp_dir().join(format!("aleo-acc-audit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        AuditLog {
+            db: Arc::new(DB::open_default(dir).unwrap()),
+        }
+    }
+
+    fn audit_record(id: &str, timestamp: &str, method: &str) -> AuditRecord {
+        AuditRecord {
+            id: id.to_string(),
+            timestamp: timestamp.to_string(),
+            client: None,
+            method: Some(method.to_string()),
+            params: BTreeMap::new(),
+            duration_ms: 1,
+            success: true,
+            error: None,
+            transaction_id: None,
+            transaction_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_query_and_retention() {
+        let audit = temp_log("query");
+        let now = Local::now();
+        for (index, method) in ["transfer", "execute", "transfer"].iter().enumerate() {
+            let time = now - Duration::minutes(3 - index as i64);
+            let record = audit_record(&index.to_string(), &time.to_rfc3339(), method);
+            audit.insert(&record).unwrap();
+        }
+        let old = audit_record("old", &(now - Duration::days(100)).to_rfc3339(), "split");
+        audit.insert(&old).unwrap();
+
+        let all = audit.query(&AuditQuery::default()).unwrap();
+        assert_eq!(
+            all.iter().map(|v| v.id.as_str()).collect::<Vec<_>>(),
+            ["2", "1", "0", "old"]
+        );
+        let transfers = audit
+            .query(&AuditQuery {
+                method: Some("transfer".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(transfers[0].id, "2");
+        assert_eq!(transfers.len(), 1);
+        let range = audit
+            .query(&AuditQuery {
+                from: Some((now - Duration::minutes(4)).to_rfc3339()),
+                to: Some((now - Duration::minutes(1)).to_rfc3339()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(range.len(), 2);
+
+        audit
+            .set_retention(&AuditRetention {
+                max_records: 2,
+                max_age_days: 90,
+            })
+            .unwrap();
+        let kept = audit.query(&AuditQuery::default()).unwrap();
+        assert_eq!(
+            kept.iter().map(|v| v.id.as_str()).collect::<Vec<_>>(),
+            ["2", "1"]
+        );
+        assert_eq!(audit.get_retention().unwrap().max_records, 2);
+
+        audit.clear().unwrap();
+        assert!(audit.query(&AuditQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_keys_sort_by_time() {
+        assert!(record_key(999, "b") < record_key(1000, "a"));
+        assert!(record_key(i64::MAX, "z").as_str() < AUDIT_KEY_END);
+        assert_eq!(key_millis(record_key(1000, "a").as_bytes()), Some(1000));
+        assert_eq!(key_millis(b"audit_retention"), None);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::fee::Fee;
+use crate::net::transaction::{self, TransactionRecord, TransactionStatus};
+
+/// `$<step>.<index>`, the `index`th record an earlier step returned
+const REFERENCE_PREFIX: char = '$';
+/// how long a step waits for the transactions it spends records of to be accepted
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(3 * 60);
+
+/// one call of `execute_bundle`, with the parameters of the rpc method it stands for.
+/// any record parameter, and any input of `execute`, may be a reference instead
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum BundleStep {
+    Execute {
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+    },
+    Transfer {
+        recipient: String,
+        amount: u64,
+        function: String,
+        input_record: Option<String>,
+        fee: Option<Fee>,
+    },
+    Join {
+        first_record: String,
+        second_record: String,
+        fee: Option<Fee>,
+    },
+    Split {
+        record: String,
+        amount: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleTransaction {
+    pub transaction: String,
+    /// records of the transaction owned by the caller, in output order
+    pub records: Vec<String>,
+    /// set when the transaction was broadcast
+    pub broadcast: Option<TransactionRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleResult {
+    pub transactions: Vec<BundleTransaction>,
+    /// steps that weren't run because a transaction they spend records of wasn't
+    /// accepted within [`CONFIRM_TIMEOUT`], to be sent as a new bundle once it is.
+    /// references to `transactions` are replaced with the records
+    pub remaining: Vec<BundleStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Reference {
+    step: usize,
+    index: usize,
+}
+
+impl BundleStep {
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::Execute { .. } => "executev2",
+            Self::Transfer { .. } => "transferv2",
+            Self::Join { .. } => "joinv2",
+            Self::Split { .. } => "splitv2",
+        }
+    }
+
+    fn record_values(&self) -> Vec<&String> {
+        match self {
+            Self::Execute { inputs, fee, .. } => inputs.iter().chain(fee_record(fee)).collect(),
+            Self::Transfer {
+                input_record, fee, ..
+            } => input_record.iter().chain(fee_record(fee)).collect(),
+            Self::Join {
+                first_record,
+                second_record,
+                fee,
+            } => [first_record, second_record]
+                .into_iter()
+                .chain(fee_record(fee))
+                .collect(),
+            Self::Split { record, .. } => vec![record],
+        }
+    }
+
+    fn record_values_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Self::Execute { inputs, fee, .. } => {
+                inputs.iter_mut().chain(fee_record_mut(fee)).collect()
+            }
+            Self::Transfer {
+                input_record, fee, ..
+            } => input_record.iter_mut().chain(fee_record_mut(fee)).collect(),
+            Self::Join {
+                first_record,
+                second_record,
+                fee,
+            } => [first_record, second_record]
+                .into_iter()
+                .chain(fee_record_mut(fee))
+                .collect(),
+            Self::Split { record, .. } => vec![record],
+        }
+    }
+
+    pub fn has_references(&self) -> bool {
+        self.record_values()
+            .iter()
+            .any(|v| v.starts_with(REFERENCE_PREFIX))
+    }
+
+    /// earlier steps this one spends records of, in order
+    fn referenced_steps(&self) -> Result<Vec<usize>> {
+        let mut steps = Vec::new();
+        for value in self.record_values() {
+            if let Some(reference) = parse_reference(value)? {
+                steps.push(reference.step);
+            }
+        }
+        steps.sort_unstable();
+        steps.dedup();
+        Ok(steps)
+    }
+
+    /// replaces the references to the steps in `done` with their records, and counts the
+    /// others from the first step after `done`
+    pub fn resolve(&mut self, done: &[BundleTransaction]) -> Result<()> {
+        for value in self.record_values_mut() {
+            let reference = match parse_reference(value)? {
+                Some(v) => v,
+                None => continue,
+            };
+            let records = match done.get(reference.step) {
+                Some(v) => &v.records,
+                None => {
+                    *value = format!(
+                        "{}{}.{}",
+                        REFERENCE_PREFIX,
+                        reference.step - done.len(),
+                        reference.index
+                    );
+                    continue;
+                }
+            };
+            *value = records
+                .get(reference.index)
+                .context(format!(
+                    "{} refers to a missing record, step {} returned {}",
+                    value,
+                    reference.step,
+                    records.len()
+                ))?
+                .clone();
+        }
+        Ok(())
+    }
+}
+
+fn fee_record(fee: &Option<Fee>) -> Option<&String> {
+    fee.as_ref().and_then(|v| v.fee_record.as_ref())
+}
+
+fn fee_record_mut(fee: &mut Option<Fee>) -> Option<&mut String> {
+    fee.as_mut().and_then(|v| v.fee_record.as_mut())
+}
+
+fn parse_reference(value: &str) -> Result<Option<Reference>> {
+    let reference = match value.strip_prefix(REFERENCE_PREFIX) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let invalid = || anyhow!("{} is not a reference like $0.1", value);
+    let (step, index) = reference.split_once('.').ok_or_else(invalid)?;
+    Ok(Some(Reference {
+        step: step.parse().map_err(|_| invalid())?,
+        index: index.parse().map_err(|_| invalid())?,
+    }))
+}
+
+/// waits until the transactions of the steps `step` spends records of are accepted,
+/// a record can only be spent once it is on chain. false when one of them is still
+/// pending after [`CONFIRM_TIMEOUT`]
+pub fn wait_for_records(step: &BundleStep, done: &mut [BundleTransaction]) -> Result<bool> {
+    let started = Instant::now();
+    for index in step.referenced_steps()? {
+        let id = done
+            .get(index)
+            .and_then(|v| v.broadcast.as_ref())
+            .map(|v| v.id.clone())
+            .context(format!("step {} wasn't broadcast", index))?;
+        let record =
+            transaction::wait_final(&id, CONFIRM_TIMEOUT.saturating_sub(started.elapsed()))?;
+        let status = record.status;
+        done[index].broadcast = Some(record);
+        match status {
+            TransactionStatus::Accepted => {}
+            TransactionStatus::Pending => return Ok(false),
+            TransactionStatus::Rejected => {
+                let broadcast: Vec<&str> = done
+                    .iter()
+                    .filter_map(|v| v.broadcast.as_ref().map(|v| v.id.as_str()))
+                    .collect();
+                return Err(anyhow!(
+                    "transaction {} of step {} was rejected, its records can't be spent. broadcast so far: {}",
+                    id,
+                    index,
+                    broadcast.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// checks the references of `steps` before anything is proven: they point to an
+/// earlier step and no record is spent twice. records are only spendable once they are
+/// on chain, so references need `broadcast`
+pub fn check_references(steps: &[BundleStep], broadcast: bool) -> Result<()> {
+    let mut spent = HashSet::new();
+    for (step, value) in steps
+        .iter()
+        .enumerate()
+        .flat_map(|(i, v)| v.record_values().into_iter().map(move |v| (i, v)))
+    {
+        let reference = match parse_reference(value)? {
+            Some(v) => v,
+            None => continue,
+        };
+        if !broadcast {
+            return Err(anyhow!(
+                "step {} spends {} of an earlier step, which needs broadcast",
+                step,
+                value
+            ));
+        }
+        if reference.step >= step {
+            return Err(anyhow!(
+                "step {} refers to {}, only records of earlier steps can be spent",
+                step,
+                value
+            ));
+        }
+        if !spent.insert(reference) {
+            return Err(anyhow!("{} is spent twice", value));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split_then_transfer() -> Vec<BundleStep> {
+        serde_json::from_str(
+            r#"[
+                { "method": "split", "record": "{ owner: aleo1.private }", "amount": 100 },
+                {
+                    "method": "transfer",
+                    "recipient": "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px",
+                    "amount": 100,
+                    "function": "private",
+                    "input_record": "$0.0",
+                    "fee": { "payment": "private", "fee_record": "$0.1" }
+                }
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            parse_reference("$2.1").unwrap(),
+            Some(Reference { step: 2, index: 1 })
+        );
+        assert_eq!(parse_reference("5u64").unwrap(), None);
+        assert!(parse_reference("$2").is_err());
+        assert!(parse_reference("$a.1").is_err());
+    }
+
+    #[test]
+    fn test_check_references() {
+        let mut steps = split_then_transfer();
+        assert!(check_references(&steps, true).is_ok());
+        assert!(check_references(&steps, false).is_err());
+        assert!(check_references(&steps[..1], false).is_ok());
+
+        if let BundleStep::Transfer { fee, .. } = &mut steps[1] {
+            fee.as_mut().unwrap().fee_record = Some("$0.0".to_string());
+        }
+        assert!(check_references(&steps, true).is_err());
+
+        let forward = BundleStep::Split {
+            record: "$1.0".to_string(),
+            amount: 1,
+        };
+        assert!(check_references(&[forward], true).is_err());
+    }
+
+    #[test]
+    fn test_referenced_steps() {
+        let steps = split_then_transfer();
+        assert!(steps[0].referenced_steps().unwrap().is_empty());
+        assert_eq!(steps[1].referenced_steps().unwrap(), [0]);
+
+        let join = BundleStep::Join {
+            first_record: "$2.0".to_string(),
+            second_record: "$0.1".to_string(),
+            fee: None,
+        };
+        assert_eq!(join.referenced_steps().unwrap(), [0, 2]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut step = split_then_transfer().remove(1);
+        assert!(step.has_references());
+        let done = vec![BundleTransaction {
+            transaction: String::new(),
+            records: vec!["first".to_string(), "second".to_string()],
+            broadcast: None,
+        }];
+        step.resolve(&done).unwrap();
+        assert_eq!(
+            step.record_values(),
+            vec![&"first".to_string(), &"second".to_string()]
+        );
+        assert!(!step.has_references());
+
+        let mut later = BundleStep::Split {
+            record: "$2.1".to_string(),
+            amount: 1,
+        };
+        later.resolve(&done).unwrap();
+        assert_eq!(later.record_values(), vec![&"$1.1".to_string()]);
+
+        let mut missing = BundleStep::Split {
+            record: "$0.2".to_string(),
+            amount: 1,
+        };
+        assert!(missing.resolve(&done).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{collections::VecDeque, sync::Mutex, time::Instant};
+
+use chrono::{Duration, Local};
+use lazy_static::lazy_static;
+use rand_core::{OsRng, RngCore};
+use serde::Serialize;
+
+use crate::{
+    service::events::{self, EVENT_REQUEST_FINISHED},
+    telemetry::SpanContext,
+    tls,
+};
+
+tokio::task_local! {
+    /// set for the whole handling of an rpc request, the logger reads it so every
+    /// line of a request carries the same correlation id
+    pub static REQUEST: RequestContext;
+}
+
+const MAX_RECENT_REQUESTS: usize = 100;
+
+lazy_static! {
+    static ref RECENT_REQUESTS: Mutex<VecDeque<RequestSummary>> = Mutex::new(VecDeque::new());
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub id: String,
+    /// first bytes of the client's public key fingerprint, enough to tell clients apart in logs
+    pub client: Option<String>,
+    /// full fingerprint of the client's public key
+    pub fingerprint: Option<String>,
+    pub method: Option<String>,
+    pub started: Instant,
+    /// span of the http request, parent of the spans recorded while handling it
+    pub trace: Option<SpanContext>,
+}
+
+impl RequestContext {
+    pub fn new(client_pubkey: Option<&[u8]>) -> Self {
+        let mut id = [0u8; 8];
+        OsRng.fill_bytes(&mut id);
+        let fingerprint = client_pubkey.map(tls::pubkey_to_fingerprint);
+        Self {
+            id: hex::encode(id),
+            client: fingerprint.as_ref().map(|v| hex::encode(&v[..8])),
+            fingerprint: fingerprint.map(hex::encode),
+            method: None,
+            started: Instant::now(),
+            trace: None,
+        }
+    }
+
+    pub fn with_trace(mut self, trace: Option<SpanContext>) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    pub fn with_method(mut self, method: Option<String>) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// context of the `index`th call of a batch request, timed from now
+    pub fn batch_call(&self, index: usize) -> Self {
+        Self {
+            id: format!("{}.{}", self.id, index),
+            started: Instant::now(),
+            ..self.clone()
+        }
+    }
+}
+
+/// context of the request being handled on this task, if any
+pub fn current() -> Option<RequestContext> {
+    REQUEST.try_with(|v| v.clone()).ok()
+}
+
+/// what is kept about a finished request, no params or results
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestSummary {
+    pub id: String,
+    pub client: Option<String>,
+    pub method: Option<String>,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub failed: bool,
+}
+
+pub fn record_finished(context: &RequestContext, failed: bool) {
+    let elapsed = context.started.elapsed();
+    let started_at =
+        Local::now() - Duration::from_std(elapsed).unwrap_or_else(|_| Duration::zero());
+    let summary = RequestSummary {
+        id: context.id.clone(),
+        client: context.client.clone(),
+        method: context.method.clone(),
+        started_at: started_at.to_rfc3339(),
+        duration_ms: elapsed.as_millis() as u64,
+        failed,
+    };
+
+    events::emit(EVENT_REQUEST_FINISHED, &summary);
+    let mut recent = RECENT_REQUESTS.lock().unwrap();
+    recent.push_back(summary);
+    if recent.len() > MAX_RECENT_REQUESTS {
+        recent.pop_front();
+    }
+}
+
+/// the last finished requests, oldest first
+pub fn recent_requests() -> Vec<RequestSummary> {
+    RECENT_REQUESTS.lock().unwrap().iter().cloned().collect()
+}
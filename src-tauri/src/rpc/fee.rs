@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeePayment {
+    /// paid from the account's public balance
+    #[default]
+    Public,
+    /// paid from `fee_record`
+    Private,
+}
+
+/// Fee of a proving rpc. The base fee is always computed from the transaction,
+/// the caller only picks the priority fee paid on top of it and how it is paid.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fee {
+    /// left out, the proving rpcs get no priority fee at all like the legacy methods did
+    pub priority_fee_in_microcredits: Option<u64>,
+    pub payment: FeePayment,
+    pub fee_record: Option<String>,
+}
+
+impl Fee {
+    /// fee of the methods taking a bare `fee` and `fee_record`, a record makes it private
+    pub fn from_legacy(fee_record: Option<String>, priority_fee: Option<u64>) -> Self {
+        Self {
+            priority_fee_in_microcredits: priority_fee,
+            payment: match fee_record {
+                Some(_) => FeePayment::Private,
+                None => FeePayment::Public,
+            },
+            fee_record,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match (self.payment, &self.fee_record) {
+            (FeePayment::Private, None) => Err(anyhow!("a private fee needs a fee record")),
+            (FeePayment::Public, Some(_)) => Err(anyhow!(
+                "a public fee is paid from the balance, not a record"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record(&self) -> Option<&str> {
+        self.fee_record.as_deref()
+    }
+
+    pub fn priority_fee(&self) -> Option<u64> {
+        self.priority_fee_in_microcredits
+    }
+
+    /// priority fee to add to the base fee, zero when none was given
+    pub fn priority_amount(&self) -> u64 {
+        self.priority_fee_in_microcredits.unwrap_or_default()
+    }
+
+    /// whether the caller asked for anything besides the default public fee
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(Fee::default().validate().is_ok());
+        let private = Fee {
+            payment: FeePayment::Private,
+            ..Default::default()
+        };
+        assert!(private.validate().is_err());
+        let public_with_record = Fee {
+            fee_record: Some("{ owner: aleo1.private }".to_string()),
+            ..Default::default()
+        };
+        assert!(public_with_record.validate().is_err());
+    }
+
+    #[test]
+    fn test_from_legacy() {
+        let fee = Fee::from_legacy(Some("{ owner: aleo1.private }".to_string()), Some(3000));
+        assert_eq!(fee.payment, FeePayment::Private);
+        assert_eq!(fee.priority_fee(), Some(3000));
+        assert!(fee.validate().is_ok());
+        assert!(Fee::from_legacy(None, None).is_default());
+        assert_eq!(Fee::from_legacy(None, None).priority_fee(), None);
+    }
+
+    #[test]
+    fn test_deserialize_partial() {
+        let fee: Fee = serde_json::from_str(r#"{ "priority_fee_in_microcredits": 500 }"#).unwrap();
+        assert_eq!(fee.payment, FeePayment::Public);
+        assert_eq!(fee.priority_fee(), Some(500));
+    }
+}
@@ -5,17 +5,36 @@ use hyper::header::{HeaderValue, CONTENT_TYPE};
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Response, StatusCode};
 use jsonrpc_core::types::request::Request as JsonRpcRequest;
-use jsonrpc_core::{Id, MethodCall, Params};
+use jsonrpc_core::{ErrorCode, Id, MethodCall, Params};
 use serde_json::{from_slice, Value};
 use tokio::sync::oneshot::Sender;
 
-use crate::tls;
+use crate::{
+    logger::with_duration,
+    stats,
+    telemetry::{Span, SpanContext, SpanKind, HEADER_TRACEPARENT},
+    tls,
+};
+
+use super::{
+    audit,
+    context::{self, RequestContext, REQUEST},
+};
 
 const CODE_AES_DECRYPT_ERROR: i64 = 1234;
 const CODE_NO_PUBKEY_FOUND: i64 = 1235;
 const CODE_ECDH_ERROR: i64 = 1236;
 
 const HEADER_PUBLIC_KEY: &str = "Public-Key";
+const HEADER_REQUEST_ID: &str = "X-Request-Id";
+const ALLOW_HEADERS: &str = "Content-Type, Public-Key, traceparent";
+
+/// what `handle_rpc` tells the middleware about the call, kept in the response extensions
+#[derive(Debug, Clone)]
+struct RpcCall {
+    method: Option<String>,
+    failed: bool,
+}
 
 async fn handle_request(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
     match (req.method(), req.uri().path()) {
@@ -23,7 +42,7 @@ async fn handle_request(req: Request<Body>) -> Result<Response<Body>, hyper::Err
             let resp = Response::builder()
                 .header("Access-Control-Allow-Origin", "*")
                 .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
-                .header("Access-Control-Allow-Headers", "Content-Type, Public-Key")
+                .header("Access-Control-Allow-Headers", ALLOW_HEADERS)
                 .body(Body::empty())
                 .unwrap();
             Ok(resp)
@@ -47,7 +66,7 @@ async fn handle_request(req: Request<Body>) -> Result<Response<Body>, hyper::Err
                     .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
                     .header("Access-Control-Allow-Origin", "*")
                     .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
-                    .header("Access-Control-Allow-Headers", "Content-Type, Public-Key")
+                    .header("Access-Control-Allow-Headers", ALLOW_HEADERS)
                     .body(Body::from(response_body))
                     .unwrap();
                 return Ok(body);
@@ -72,7 +91,11 @@ async fn handle_request(req: Request<Body>) -> Result<Response<Body>, hyper::Err
             );
             headers.insert(
                 "Access-Control-Allow-Headers",
-                "Content-Type, Public-Key".parse().unwrap(),
+                ALLOW_HEADERS.parse().unwrap(),
+            );
+            headers.insert(
+                "Access-Control-Expose-Headers",
+                HEADER_REQUEST_ID.parse().unwrap(),
             );
 
             Ok(response)
@@ -85,10 +108,47 @@ async fn handle_request(req: Request<Body>) -> Result<Response<Body>, hyper::Err
 }
 
 async fn aes_decode_middleware(req: Request<Body>) -> Response<Body> {
+    let pk = read_header_hex_as_bytes(req.headers().get(HEADER_PUBLIC_KEY));
+
+    // a caller that traces its own requests passes its span in `traceparent`
+    let parent = req
+        .headers()
+        .get(HEADER_TRACEPARENT)
+        .and_then(|v| v.to_str().ok())
+        .and_then(SpanContext::from_traceparent);
+    let mut span = Span::start("rpc request", SpanKind::Server, parent);
+
+    // every log line until the response is sent carries this request's id
+    let context = RequestContext::new(pk.as_deref().ok()).with_trace(span.context());
+    let request_id = context.id.clone();
+
+    let mut response = REQUEST.scope(context, decode_request(req, pk)).await;
+    if let Ok(v) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(HEADER_REQUEST_ID, v);
+    }
+
+    span.set_attribute("request.id", request_id);
+    span.set_attribute("http.status_code", response.status().as_u16());
+    match response.extensions().get::<RpcCall>() {
+        Some(call) => {
+            if let Some(method) = &call.method {
+                span.set_attribute("rpc.method", method.as_str());
+            }
+            if call.failed {
+                span.set_error("rpc call failed");
+            }
+        }
+        None => span.set_error("request not handled"),
+    }
+    span.end();
+    response
+}
+
+async fn decode_request(req: Request<Body>, pk: Result<Vec<u8>>) -> Response<Body> {
     let (parts, body) = req.into_parts();
     let body_bytes = hyper::body::to_bytes(body).await.unwrap();
 
-    let pk = match read_header_hex_as_bytes(parts.headers.get(HEADER_PUBLIC_KEY)) {
+    let pk = match pk {
         Ok(v) => v,
         Err(e) => {
             let mut error = jsonrpc_core::error::Error::new(jsonrpc_core::ErrorCode::ServerError(
@@ -114,7 +174,15 @@ async fn aes_decode_middleware(req: Request<Body>) -> Response<Body> {
 
     let aes_key = tls::shared_secret_to_symmetric_secret(&shared);
 
-    let decoded_body = match tls::aes::aes_decode(&aes_key, &body_bytes) {
+    let mut span = Span::child("decrypt request", SpanKind::Internal);
+    span.set_attribute("body.size", body_bytes.len());
+    let decoded_body = tls::aes::aes_decode(&aes_key, &body_bytes);
+    if let Err(e) = &decoded_body {
+        span.set_error(e);
+    }
+    span.end();
+
+    let decoded_body = match decoded_body {
         Ok(v) => v,
         Err(e) => {
             let mut error = jsonrpc_core::error::Error::new(jsonrpc_core::ErrorCode::ServerError(
@@ -161,15 +229,36 @@ async fn handle_rpc(req: Request<Body>) -> Response<Body> {
         }
     };
 
-    let res = super::rpc::RPC_HANDER
-        .handle_rpc_request(decoded_body)
-        .await;
+    let context = context::current().unwrap_or_else(|| RequestContext::new(None));
+    // each call of a batch is logged, counted and audited on its own
+    let (res, method, failed) = match decoded_body {
+        JsonRpcRequest::Single(call) => {
+            let method = call_method(&call);
+            let output = handle_call(context.with_method(method.clone()), call).await;
+            let failed = matches!(&output, Some(jsonrpc_core::Output::Failure(_)));
+            (output.map(jsonrpc_core::Response::Single), method, failed)
+        }
+        JsonRpcRequest::Batch(calls) => {
+            let mut outputs = Vec::new();
+            for (index, call) in calls.into_iter().enumerate() {
+                let call_context = context.batch_call(index).with_method(call_method(&call));
+                outputs.extend(handle_call(call_context, call).await);
+            }
+            let failed = outputs
+                .iter()
+                .any(|v| matches!(v, jsonrpc_core::Output::Failure(_)));
+            let res = (!outputs.is_empty()).then_some(jsonrpc_core::Response::Batch(outputs));
+            (res, Some("batch".to_string()), failed)
+        }
+    };
 
+    let call = RpcCall { method, failed };
     if let Some(response) = res {
         let response_body = serde_json::to_string(&response).unwrap_or_default();
         let body = Response::builder()
             .status(StatusCode::OK)
             .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .extension(call)
             .body(Body::from(response_body))
             .unwrap();
         return body;
@@ -179,12 +268,59 @@ async fn handle_rpc(req: Request<Body>) -> Response<Body> {
     let response = Response::builder()
         .status(StatusCode::OK)
         .header(CONTENT_TYPE, HeaderValue::from_static("text/plain"))
+        .extension(call)
         .body(Body::from("Response"))
         .unwrap();
 
     response
 }
 
+/// handles one call under `context`, and records it in the stats and audit trail
+async fn handle_call(
+    context: RequestContext,
+    call: jsonrpc_core::Call,
+) -> Option<jsonrpc_core::Output> {
+    let params = audit::request_params(&call);
+    let output = REQUEST
+        .scope(context.clone(), super::rpc::RPC_HANDER.handle_call(call))
+        .await;
+
+    let error = match &output {
+        Some(jsonrpc_core::Output::Failure(v)) => Some(&v.error),
+        _ => None,
+    };
+    let failed = error.is_some();
+    REQUEST.sync_scope(context.clone(), || {
+        with_duration(
+            context.started.elapsed(),
+            || log::info!(target: "rpc", "request finished, failed: {}", failed),
+        )
+    });
+    context::record_finished(&context, failed);
+    // unknown methods would add an entry per typo
+    match (&context.method, error) {
+        (_, Some(e)) if e.code == ErrorCode::MethodNotFound => {}
+        (Some(method), _) => stats::record(
+            method,
+            context.started.elapsed(),
+            error.map(|v| v.message.as_str()),
+        ),
+        _ => {}
+    }
+    REQUEST.sync_scope(context.clone(), || {
+        audit::record(&context, params, output.as_ref())
+    });
+    output
+}
+
+fn call_method(call: &jsonrpc_core::Call) -> Option<String> {
+    match call {
+        jsonrpc_core::Call::MethodCall(v) => Some(v.method.clone()),
+        jsonrpc_core::Call::Notification(v) => Some(v.method.clone()),
+        jsonrpc_core::Call::Invalid { .. } => None,
+    }
+}
+
 pub fn start_hyper(address: &SocketAddr) -> Sender<()> {
     // Create the Hyper server
     let make_svc =
@@ -1,5 +1,10 @@
+pub mod audit;
+pub mod bundle;
+pub mod context;
+pub mod fee;
 pub mod middleware;
 pub mod rpc;
+pub mod transfer;
 
 use std::{
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
@@ -9,7 +14,7 @@ use std::{
 use lazy_static::lazy_static;
 use tokio::sync::oneshot::Sender;
 
-use crate::config::consts::RPC_PORT;
+use crate::config::profile::PROFILE;
 
 lazy_static! {
     static ref RPC_CLOSER: Mutex<Option<Sender<()>>> = Mutex::new(None);
@@ -27,20 +32,25 @@ pub fn stop_rpc_server() {
     }
 }
 
+pub fn is_rpc_server_running() -> bool {
+    RPC_CLOSER.lock().unwrap().is_some()
+}
+
 #[tauri::command]
 pub async fn run_rpc_server() {
     if RPC_CLOSER.lock().unwrap().is_some() {
         return;
     }
+    let port = PROFILE.rpc_port();
+
     #[cfg(debug_assertions)]
-    let address: SocketAddr =
-        SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), RPC_PORT));
+    let address: SocketAddr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), port));
 
     #[cfg(not(debug_assertions))]
-    let address = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), RPC_PORT));
+    let address = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port));
 
     let close = middleware::start_hyper(&address);
     let mut rpc_closer = RPC_CLOSER.lock().unwrap();
     *rpc_closer = Some(close);
-    log::info!("rpc server started!");
+    log::info!("rpc server started on port {}!", port);
 }
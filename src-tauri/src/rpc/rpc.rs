@@ -6,16 +6,55 @@ use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
-use crate::{config::Config, service::app::update_dialog, tls};
+use super::{
+    bundle::{self, BundleResult, BundleStep, BundleTransaction},
+    fee::Fee,
+    transfer::TransferFunction,
+};
+use crate::{
+    aleo::{
+        account::{self, Account},
+        authorize::{self, DeployAuthorization},
+        cost::{self, CostBreakdown},
+        parameters,
+        prove::{self, ProvingState},
+        scan::{self, ScanResult},
+        verify::{self, VerificationReport},
+    },
+    config::{network::NetworkKind, Config},
+    net::{
+        programs,
+        relay::{relay_url, snapshot_url},
+        snapshot::StateSnapshot,
+        transaction::{self, TransactionRecord},
+    },
+    service::app::update_dialog,
+    stats::{self, StatsReport},
+    tls,
+};
 
+/// calls an aleowrap function, or one of `crate::aleo` when given as `module::function`
 macro_rules! call_aleo_function {
     ($func:ident($($arg:expr),*)) => {
+        call_aleo_function!(aleowrap::$func($($arg),*))
+    };
+    ($module:ident::$func:ident($($arg:expr),*)) => {
         {
             let start_time = Instant::now();
             log::info!(target: "aleosdk","executing method '{}'",stringify!($func));
-            let result  = aleowrap::$func($($arg),*);
+            let mut span = crate::telemetry::Span::child(
+                concat!(stringify!($module), " ", stringify!($func)),
+                crate::telemetry::SpanKind::Internal,
+            );
+            let result  = $module::$func($($arg),*);
             let elapsed_time = Instant::now() - start_time;
-            log::info!(target: "aleosdk","method '{}' took {} ms", stringify!($func),elapsed_time.as_millis());
+            if let Err(e) = &result {
+                span.set_error(crate::logger::redact::redact(&e.to_string()));
+            }
+            span.end();
+            crate::logger::with_duration(elapsed_time, || {
+                log::info!(target: "aleosdk","method '{}' took {} ms", stringify!($func),elapsed_time.as_millis())
+            });
             result
         }
     };
@@ -33,8 +72,67 @@ pub fn init_rpc_hander() -> IoHandler {
 
 #[rpc]
 pub trait Rpc {
-    #[rpc(name = "deploy")]
+    #[rpc(name = "deployv2")]
     fn deploy(
+        &self,
+        private_key: String,
+        program: String,
+        imports: Option<HashMap<String, String>>,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String>;
+
+    #[rpc(name = "executev2")]
+    fn execute(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String>;
+
+    #[rpc(name = "transferv2")]
+    fn transfer(
+        &self,
+        private_key: String,
+        recipient: String,
+        amount: u64,
+        function: String,
+        input_record: Option<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String>;
+
+    #[rpc(name = "joinv2")]
+    fn join(
+        &self,
+        private_key: String,
+        first_record: String,
+        second_record: String,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String>;
+
+    #[rpc(name = "splitv2")]
+    fn split(
+        &self,
+        private_key: String,
+        record: String,
+        amount: u64,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String>;
+
+    /// deprecated, use `deployv2`
+    #[rpc(name = "deploy")]
+    fn deploy_legacy(
         &self,
         private_key: String,
         program: String,
@@ -42,10 +140,12 @@ pub trait Rpc {
         imports: Option<HashMap<String, String>>,
         priority_fee_in_microcredits: Option<u64>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    /// deprecated, use `executev2`
     #[rpc(name = "execute")]
-    fn execute(
+    fn execute_legacy(
         &self,
         private_key: String,
         program_id: String,
@@ -54,10 +154,12 @@ pub trait Rpc {
         record: Option<String>,
         fee: Option<u64>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    /// deprecated, use `transferv2`
     #[rpc(name = "transfer")]
-    fn transfer(
+    fn transfer_legacy(
         &self,
         private_key: String,
         recipient: String,
@@ -67,10 +169,12 @@ pub trait Rpc {
         fee_record: Option<String>,
         fee: Option<u64>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    /// deprecated, use `joinv2`
     #[rpc(name = "join")]
-    fn join(
+    fn join_legacy(
         &self,
         private_key: String,
         first_record: String,
@@ -78,15 +182,18 @@ pub trait Rpc {
         fee_record: Option<String>,
         fee: Option<u64>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    /// deprecated, use `splitv2`
     #[rpc(name = "split")]
-    fn split(
+    fn split_legacy(
         &self,
         private_key: String,
         record: String,
         amount: u64,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
     #[rpc(name = "deployment_cost")]
@@ -103,8 +210,28 @@ pub trait Rpc {
         function: String,
         inputs: Vec<String>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    #[rpc(name = "deployment_costv2")]
+    fn deployment_cost_breakdown(
+        &self,
+        program: String,
+        imports: Option<HashMap<String, String>>,
+        fee: Option<Fee>,
+    ) -> Result<CostBreakdown>;
+
+    #[rpc(name = "execution_costv3")]
+    fn execution_cost_breakdown(
+        &self,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<CostBreakdown>;
+
     #[rpc(name = "decrypt_recordsv2")]
     fn decrypt_records(&self, view_key: String, records: Vec<String>) -> Result<Vec<String>>;
 
@@ -115,6 +242,7 @@ pub trait Rpc {
         execute_authorization_str: String,
         fee_authorization_str: String,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
     #[rpc(name = "deploy_from_authorization")]
@@ -125,20 +253,124 @@ pub trait Rpc {
         owner_str: String,
         fee_authorization_str: String,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String>;
 
+    #[rpc(name = "authorize_execute")]
+    fn authorize_execute(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        query: Option<String>,
+    ) -> Result<String>;
+
+    #[rpc(name = "authorize_fee")]
+    fn authorize_fee(
+        &self,
+        private_key: String,
+        execute_authorization_str: String,
+        base_fee_in_microcredits: Option<u64>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<String>;
+
+    #[rpc(name = "authorize_deploy_fee")]
+    fn authorize_deploy_fee(
+        &self,
+        private_key: String,
+        program: String,
+        imports: Option<HashMap<String, String>>,
+        base_fee_in_microcredits: Option<u64>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<DeployAuthorization>;
+
+    #[rpc(name = "broadcast")]
+    fn broadcast(&self, transaction: String, query: Option<String>) -> Result<TransactionRecord>;
+
+    #[rpc(name = "execute_and_broadcast")]
+    fn execute_and_broadcast(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<TransactionRecord>;
+
+    #[rpc(name = "transaction_status")]
+    fn transaction_status(
+        &self,
+        transaction_id: String,
+        query: Option<String>,
+    ) -> Result<TransactionRecord>;
+
+    #[rpc(name = "verify_transaction")]
+    fn verify_transaction(
+        &self,
+        transaction: String,
+        query: Option<String>,
+    ) -> Result<VerificationReport>;
+
+    #[rpc(name = "execute_bundle")]
+    fn execute_bundle(
+        &self,
+        private_key: String,
+        steps: Vec<BundleStep>,
+        broadcast: Option<bool>,
+        query: Option<String>,
+    ) -> Result<BundleResult>;
+
+    #[rpc(name = "scan_records")]
+    fn scan_records(
+        &self,
+        view_key: String,
+        start_height: u32,
+        end_height: u32,
+        query: Option<String>,
+    ) -> Result<ScanResult>;
+
+    #[rpc(name = "generate_account")]
+    fn generate_account(&self) -> Result<Account>;
+
+    #[rpc(name = "derive_account")]
+    fn derive_account(&self, private_key: String) -> Result<Account>;
+
+    #[rpc(name = "sign")]
+    fn sign(&self, private_key: String, message: String) -> Result<String>;
+
+    #[rpc(name = "verify")]
+    fn verify(&self, address: String, message: String, signature: String) -> Result<bool>;
+
     #[rpc(name = "discovery")]
     fn discovery(&self) -> Result<Discovery>;
 
     #[rpc(name = "update")]
     fn update(&self, version: String) -> Result<()>;
+
+    #[rpc(name = "stats")]
+    fn stats(&self) -> Result<StatsReport>;
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Discovery {
     version: String,
     features: Vec<String>,
+    /// `function`s `transferv2` takes
+    transfer_functions: Vec<TransferFunction>,
     pubkey: String,
+    network: Option<ActiveNetwork>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ActiveNetwork {
+    name: String,
+    kind: NetworkKind,
+    query: Option<String>,
+    path: String,
 }
 
 pub struct RpcImpl;
@@ -148,22 +380,33 @@ impl Rpc for RpcImpl {
         &self,
         private_key: String,
         program: String,
-        fee_record: Option<String>,
         imports: Option<HashMap<String, String>>,
-        priority_fee_in_microcredits: Option<u64>,
+        fee: Option<Fee>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
-        log::info!(target: "rpc","executing rpc method 'deploy'");
+        log::info!(target: "rpc","executing rpc method 'deployv2'");
+        let fee = check_fee(fee).log_rpc_error("deployv2")?;
+        let imports = match snapshot {
+            // imports missing from the snapshot fail the deployment, nothing is fetched
+            Some(_) => imports,
+            None => resolve_imports(&program, imports, query.as_deref())
+                .to_jsonrpc_result()
+                .log_rpc_error("deployv2")?,
+        };
+        let query = resolve_query(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("deployv2")?;
         call_aleo_function!(deploy(
             &private_key,
             &program,
-            fee_record.as_deref(),
+            fee.record(),
             imports,
-            priority_fee_in_microcredits,
+            fee.priority_fee(),
             query.as_deref()
         ))
         .to_jsonrpc_result()
-        .log_rpc_error("deploy")
+        .log_rpc_error("deployv2")
     }
 
     fn execute(
@@ -172,22 +415,25 @@ impl Rpc for RpcImpl {
         program_id: String,
         function: String,
         inputs: Vec<String>,
-        record: Option<String>,
-        fee: Option<u64>,
+        fee: Option<Fee>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
-        log::info!(target: "rpc","executing rpc method 'execute'");
-        call_aleo_function!(execute(
+        log::info!(target: "rpc","executing rpc method 'executev2'");
+        let fee = check_fee(fee).log_rpc_error("executev2")?;
+        let state = resolve_state(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("executev2")?;
+        call_aleo_function!(prove::execute(
             &private_key,
             &program_id,
             &function,
             inputs,
-            record.as_deref(),
-            fee,
-            query.as_deref()
+            Some(&fee),
+            &state
         ))
         .to_jsonrpc_result()
-        .log_rpc_error("execute")
+        .log_rpc_error("executev2")
     }
 
     fn transfer(
@@ -197,23 +443,34 @@ impl Rpc for RpcImpl {
         amount: u64,
         function: String,
         input_record: Option<String>,
-        fee_record: Option<String>,
-        fee: Option<u64>,
+        fee: Option<Fee>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
-        log::info!(target: "rpc","executing rpc method 'transfer'");
-        call_aleo_function!(transfer(
+        log::info!(target: "rpc","executing rpc method 'transferv2'");
+        let fee = check_fee(fee).log_rpc_error("transferv2")?;
+        let function = function
+            .parse::<TransferFunction>()
+            .and_then(|v| {
+                v.validate(&recipient, amount, input_record.as_deref())?;
+                Ok(v)
+            })
+            .to_jsonrpc_result()
+            .log_rpc_error("transferv2")?;
+        let state = resolve_state(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("transferv2")?;
+        call_aleo_function!(prove::transfer(
             &private_key,
+            function,
             &recipient,
             amount,
-            &function,
             input_record.as_deref(),
-            fee_record.as_deref(),
-            fee,
-            query.as_deref()
+            &fee,
+            &state
         ))
         .to_jsonrpc_result()
-        .log_rpc_error("transfer")
+        .log_rpc_error("transferv2")
     }
 
     fn join(
@@ -221,21 +478,24 @@ impl Rpc for RpcImpl {
         private_key: String,
         first_record: String,
         second_record: String,
-        fee_record: Option<String>,
-        fee: Option<u64>,
+        fee: Option<Fee>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
-        log::info!(target: "rpc","executing rpc method 'join'");
-        call_aleo_function!(join(
+        log::info!(target: "rpc","executing rpc method 'joinv2'");
+        let fee = check_fee(fee).log_rpc_error("joinv2")?;
+        let state = resolve_state(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("joinv2")?;
+        call_aleo_function!(prove::join(
             &private_key,
             &first_record,
             &second_record,
-            fee_record.as_deref(),
-            fee,
-            query.as_deref()
+            &fee,
+            &state
         ))
         .to_jsonrpc_result()
-        .log_rpc_error("join")
+        .log_rpc_error("joinv2")
     }
 
     fn split(
@@ -243,12 +503,124 @@ impl Rpc for RpcImpl {
         private_key: String,
         record: String,
         amount: u64,
+        fee: Option<Fee>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
-        log::info!(target: "rpc","executing rpc method 'split'");
-        call_aleo_function!(split(&private_key, &record, amount, query.as_deref()))
+        log::info!(target: "rpc","executing rpc method 'splitv2'");
+        // credits.aleo/split is the one function without a fee
+        check_no_fee(fee).log_rpc_error("splitv2")?;
+        let state = resolve_state(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("splitv2")?;
+        call_aleo_function!(prove::split(&private_key, &record, amount, &state))
             .to_jsonrpc_result()
-            .log_rpc_error("split")
+            .log_rpc_error("splitv2")
+    }
+
+    fn deploy_legacy(
+        &self,
+        private_key: String,
+        program: String,
+        fee_record: Option<String>,
+        imports: Option<HashMap<String, String>>,
+        priority_fee_in_microcredits: Option<u64>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String> {
+        log::warn!(target: "rpc","rpc method 'deploy' is deprecated, use 'deployv2'");
+        let fee = Fee::from_legacy(fee_record, priority_fee_in_microcredits);
+        self.deploy(private_key, program, imports, Some(fee), query, snapshot)
+    }
+
+    fn execute_legacy(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        record: Option<String>,
+        fee: Option<u64>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String> {
+        log::warn!(target: "rpc","rpc method 'execute' is deprecated, use 'executev2'");
+        let fee = Fee::from_legacy(record, fee);
+        self.execute(
+            private_key,
+            program_id,
+            function,
+            inputs,
+            Some(fee),
+            query,
+            snapshot,
+        )
+    }
+
+    fn transfer_legacy(
+        &self,
+        private_key: String,
+        recipient: String,
+        amount: u64,
+        function: String,
+        input_record: Option<String>,
+        fee_record: Option<String>,
+        fee: Option<u64>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String> {
+        log::warn!(target: "rpc","rpc method 'transfer' is deprecated, use 'transferv2'");
+        let fee = Fee::from_legacy(fee_record, fee);
+        // 'transfer' took a record for public transfers too and ignored it
+        let input_record = input_record.filter(|_| {
+            function
+                .parse::<TransferFunction>()
+                .map_or(true, |v| v.spends_record())
+        });
+        self.transfer(
+            private_key,
+            recipient,
+            amount,
+            function,
+            input_record,
+            Some(fee),
+            query,
+            snapshot,
+        )
+    }
+
+    fn join_legacy(
+        &self,
+        private_key: String,
+        first_record: String,
+        second_record: String,
+        fee_record: Option<String>,
+        fee: Option<u64>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String> {
+        log::warn!(target: "rpc","rpc method 'join' is deprecated, use 'joinv2'");
+        let fee = Fee::from_legacy(fee_record, fee);
+        self.join(
+            private_key,
+            first_record,
+            second_record,
+            Some(fee),
+            query,
+            snapshot,
+        )
+    }
+
+    fn split_legacy(
+        &self,
+        private_key: String,
+        record: String,
+        amount: u64,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<String> {
+        log::warn!(target: "rpc","rpc method 'split' is deprecated, use 'splitv2'");
+        self.split(private_key, record, amount, None, query, snapshot)
     }
 
     fn deployment_cost(
@@ -257,6 +629,9 @@ impl Rpc for RpcImpl {
         imports: Option<HashMap<String, String>>,
     ) -> Result<String> {
         log::info!(target: "rpc","executing rpc method 'deployment_cost'");
+        let imports = resolve_imports(&program, imports, None)
+            .to_jsonrpc_result()
+            .log_rpc_error("deployment_cost")?;
         call_aleo_function!(deployment_cost(&program, imports))
             .to_jsonrpc_result()
             .log_rpc_error("deployment_cost")
@@ -268,8 +643,12 @@ impl Rpc for RpcImpl {
         function: String,
         inputs: Vec<String>,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
         log::info!(target: "rpc","executing rpc method 'execution_cost'");
+        let query = resolve_query(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("execution_cost")?;
         call_aleo_function!(execution_cost(
             &program_id,
             &function,
@@ -280,6 +659,66 @@ impl Rpc for RpcImpl {
         .log_rpc_error("execution_cost")
     }
 
+    fn deployment_cost_breakdown(
+        &self,
+        program: String,
+        imports: Option<HashMap<String, String>>,
+        fee: Option<Fee>,
+    ) -> Result<CostBreakdown> {
+        log::info!(target: "rpc","executing rpc method 'deployment_costv2'");
+        let fee = check_fee(fee).log_rpc_error("deployment_costv2")?;
+        let imports = resolve_imports(&program, imports, None)
+            .to_jsonrpc_result()
+            .log_rpc_error("deployment_costv2")?;
+        let base_fee = call_aleo_function!(deployment_cost(&program, imports))
+            .and_then(parse_cost)
+            .to_jsonrpc_result()
+            .log_rpc_error("deployment_costv2")?;
+        call_aleo_function!(cost::deployment_cost(
+            &program,
+            base_fee,
+            fee.priority_amount()
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("deployment_costv2")
+    }
+
+    fn execution_cost_breakdown(
+        &self,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+        snapshot: Option<StateSnapshot>,
+    ) -> Result<CostBreakdown> {
+        log::info!(target: "rpc","executing rpc method 'execution_costv3'");
+        let fee = check_fee(fee).log_rpc_error("execution_costv3")?;
+        let query = resolve_query(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("execution_costv3")?;
+        let base_fee = call_aleo_function!(execution_cost(
+            &program_id,
+            &function,
+            inputs,
+            query.as_deref()
+        ))
+        .and_then(parse_cost)
+        .to_jsonrpc_result()
+        .log_rpc_error("execution_costv3")?;
+        // programs come through the relay too, so a snapshot's programs are used
+        let endpoints: Vec<String> = query.into_iter().collect();
+        call_aleo_function!(cost::execution_cost(
+            &program_id,
+            &function,
+            base_fee,
+            fee.priority_amount(),
+            &endpoints
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("execution_costv3")
+    }
+
     fn decrypt_records(&self, view_key: String, records: Vec<String>) -> Result<Vec<String>> {
         log::info!(target: "rpc","executing rpc method 'decrypt_records'");
         call_aleo_function!(decrypt_records(&view_key, records))
@@ -293,13 +732,17 @@ impl Rpc for RpcImpl {
         execute_authorization_str: String,
         fee_authorization_str: String,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
         log::info!(target: "rpc","executing rpc method 'transaction_from_authorization'");
-        call_aleo_function!(transaction_for_authorize(
+        let state = resolve_state(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("transaction_from_authorization")?;
+        call_aleo_function!(prove::transaction_from_authorization(
             &program_id,
             &execute_authorization_str,
             &fee_authorization_str,
-            query.as_deref()
+            &state
         ))
         .to_jsonrpc_result()
         .log_rpc_error("transaction_from_authorization")
@@ -312,8 +755,18 @@ impl Rpc for RpcImpl {
         owner_str: String,
         fee_authorization_str: String,
         query: Option<String>,
+        snapshot: Option<StateSnapshot>,
     ) -> Result<String> {
         log::info!(target: "rpc","executing rpc method 'deploy_from_authorization'");
+        let imports = match snapshot {
+            Some(_) => imports,
+            None => resolve_imports(&program, imports, query.as_deref())
+                .to_jsonrpc_result()
+                .log_rpc_error("deploy_from_authorization")?,
+        };
+        let query = resolve_query(query, snapshot)
+            .to_jsonrpc_result()
+            .log_rpc_error("deploy_from_authorization")?;
         call_aleo_function!(deploy_for_authorize(
             &program,
             imports,
@@ -325,9 +778,350 @@ impl Rpc for RpcImpl {
         .log_rpc_error("deploy_from_authorization")
     }
 
+    fn authorize_execute(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        query: Option<String>,
+    ) -> Result<String> {
+        log::info!(target: "rpc","executing rpc method 'authorize_execute'");
+        let endpoints = Config::get_config().query_endpoints(query.as_deref());
+        call_aleo_function!(authorize::authorize_execute(
+            &private_key,
+            &program_id,
+            &function,
+            inputs,
+            &endpoints
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("authorize_execute")
+    }
+
+    fn authorize_fee(
+        &self,
+        private_key: String,
+        execute_authorization_str: String,
+        base_fee_in_microcredits: Option<u64>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<String> {
+        log::info!(target: "rpc","executing rpc method 'authorize_fee'");
+        let fee = check_fee(fee).log_rpc_error("authorize_fee")?;
+        let base_fee = match base_fee_in_microcredits {
+            Some(v) => v,
+            // estimated like execution_costv2, by running the authorized call
+            None => {
+                let call = authorize::first_call(&execute_authorization_str)
+                    .to_jsonrpc_result()
+                    .log_rpc_error("authorize_fee")?;
+                let query = resolve_query(query, None)
+                    .to_jsonrpc_result()
+                    .log_rpc_error("authorize_fee")?;
+                call_aleo_function!(execution_cost(
+                    &call.program_id,
+                    &call.function,
+                    call.inputs,
+                    query.as_deref()
+                ))
+                .and_then(parse_cost)
+                .to_jsonrpc_result()
+                .log_rpc_error("authorize_fee")?
+            }
+        };
+        call_aleo_function!(authorize::authorize_fee(
+            &private_key,
+            &execute_authorization_str,
+            base_fee,
+            &fee
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("authorize_fee")
+    }
+
+    fn authorize_deploy_fee(
+        &self,
+        private_key: String,
+        program: String,
+        imports: Option<HashMap<String, String>>,
+        base_fee_in_microcredits: Option<u64>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<DeployAuthorization> {
+        log::info!(target: "rpc","executing rpc method 'authorize_deploy_fee'");
+        let fee = check_fee(fee).log_rpc_error("authorize_deploy_fee")?;
+        let imports = resolve_imports(&program, imports, query.as_deref())
+            .to_jsonrpc_result()
+            .log_rpc_error("authorize_deploy_fee")?
+            .unwrap_or_default();
+        let base_fee = match base_fee_in_microcredits {
+            Some(v) => v,
+            None => call_aleo_function!(deployment_cost(&program, Some(imports.clone())))
+                .and_then(parse_cost)
+                .to_jsonrpc_result()
+                .log_rpc_error("authorize_deploy_fee")?,
+        };
+        let endpoints = Config::get_config().query_endpoints(query.as_deref());
+        call_aleo_function!(authorize::authorize_deploy_fee(
+            &private_key,
+            &program,
+            &imports,
+            base_fee,
+            &fee,
+            &endpoints
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("authorize_deploy_fee")
+    }
+
+    fn broadcast(&self, transaction: String, query: Option<String>) -> Result<TransactionRecord> {
+        log::info!(target: "rpc","executing rpc method 'broadcast'");
+        transaction::broadcast(&transaction, query.as_deref())
+            .to_jsonrpc_result()
+            .log_rpc_error("broadcast")
+    }
+
+    fn execute_and_broadcast(
+        &self,
+        private_key: String,
+        program_id: String,
+        function: String,
+        inputs: Vec<String>,
+        fee: Option<Fee>,
+        query: Option<String>,
+    ) -> Result<TransactionRecord> {
+        log::info!(target: "rpc","executing rpc method 'execute_and_broadcast'");
+        let fee = check_fee(fee).log_rpc_error("execute_and_broadcast")?;
+        let state = resolve_state(query.clone(), None)
+            .to_jsonrpc_result()
+            .log_rpc_error("execute_and_broadcast")?;
+        let transaction = call_aleo_function!(prove::execute(
+            &private_key,
+            &program_id,
+            &function,
+            inputs,
+            Some(&fee),
+            &state
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("execute_and_broadcast")?;
+        transaction::broadcast(&transaction, query.as_deref())
+            .to_jsonrpc_result()
+            .log_rpc_error("execute_and_broadcast")
+    }
+
+    fn transaction_status(
+        &self,
+        transaction_id: String,
+        query: Option<String>,
+    ) -> Result<TransactionRecord> {
+        log::info!(target: "rpc","executing rpc method 'transaction_status'");
+        transaction::transaction_status(&transaction_id, query.as_deref())
+            .to_jsonrpc_result()
+            .log_rpc_error("transaction_status")
+    }
+
+    /// synthesizes the keys of functions without a cached verifying key, which can take
+    /// minutes for a large program the first time
+    fn verify_transaction(
+        &self,
+        transaction: String,
+        query: Option<String>,
+    ) -> Result<VerificationReport> {
+        log::info!(target: "rpc","executing rpc method 'verify_transaction'");
+        let endpoints = Config::get_config().query_endpoints(query.as_deref());
+        call_aleo_function!(verify::verify_transaction(&transaction, &endpoints))
+            .to_jsonrpc_result()
+            .log_rpc_error("verify_transaction")
+    }
+
+    /// runs `steps` in order. a step spending records of an earlier step waits for that
+    /// step's transaction to be accepted, the records need a state path. when that takes
+    /// longer than `bundle::CONFIRM_TIMEOUT` the steps from there on are returned to be
+    /// sent again
+    fn execute_bundle(
+        &self,
+        private_key: String,
+        steps: Vec<BundleStep>,
+        broadcast: Option<bool>,
+        query: Option<String>,
+    ) -> Result<BundleResult> {
+        log::info!(target: "rpc","executing rpc method 'execute_bundle'");
+        let broadcast = broadcast.unwrap_or_default();
+        bundle::check_references(&steps, broadcast)
+            .to_jsonrpc_result()
+            .log_rpc_error("execute_bundle")?;
+        let mut transactions: Vec<BundleTransaction> = Vec::new();
+        let mut remaining = Vec::new();
+        let mut steps = steps.into_iter();
+        while let Some(mut step) = steps.next() {
+            let index = transactions.len();
+            if step.has_references() {
+                let accepted = bundle::wait_for_records(&step, &mut transactions)
+                    .to_jsonrpc_result()
+                    .log_rpc_error("execute_bundle")?;
+                if !accepted {
+                    remaining.push(step);
+                    remaining.extend(steps.by_ref());
+                    break;
+                }
+                step.resolve(&transactions)
+                    .to_jsonrpc_result()
+                    .log_rpc_error("execute_bundle")?;
+            }
+            let method = step.method();
+            let transaction = match step {
+                BundleStep::Execute {
+                    program_id,
+                    function,
+                    inputs,
+                    fee,
+                } => self.execute(
+                    private_key.clone(),
+                    program_id,
+                    function,
+                    inputs,
+                    fee,
+                    query.clone(),
+                    None,
+                ),
+                BundleStep::Transfer {
+                    recipient,
+                    amount,
+                    function,
+                    input_record,
+                    fee,
+                } => self.transfer(
+                    private_key.clone(),
+                    recipient,
+                    amount,
+                    function,
+                    input_record,
+                    fee,
+                    query.clone(),
+                    None,
+                ),
+                BundleStep::Join {
+                    first_record,
+                    second_record,
+                    fee,
+                } => self.join(
+                    private_key.clone(),
+                    first_record,
+                    second_record,
+                    fee,
+                    query.clone(),
+                    None,
+                ),
+                BundleStep::Split { record, amount } => self.split(
+                    private_key.clone(),
+                    record,
+                    amount,
+                    None,
+                    query.clone(),
+                    None,
+                ),
+            }
+            .map_err(|mut e| {
+                e.message = format!("step {} ({}): {}", index, method, e.message);
+                e
+            })?;
+            let records = account::owned_records(&private_key, &transaction)
+                .to_jsonrpc_result()
+                .log_rpc_error("execute_bundle")?;
+            let record = match broadcast {
+                true => Some(
+                    transaction::broadcast(&transaction, query.as_deref())
+                        .to_jsonrpc_result()
+                        .log_rpc_error("execute_bundle")?,
+                ),
+                false => None,
+            };
+            transactions.push(BundleTransaction {
+                transaction,
+                records,
+                broadcast: record,
+            });
+        }
+        let remaining = remaining
+            .into_iter()
+            .map(|mut v| {
+                v.resolve(&transactions)?;
+                Ok(v)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .to_jsonrpc_result()
+            .log_rpc_error("execute_bundle")?;
+        Ok(BundleResult {
+            transactions,
+            remaining,
+        })
+    }
+
+    fn scan_records(
+        &self,
+        view_key: String,
+        start_height: u32,
+        end_height: u32,
+        query: Option<String>,
+    ) -> Result<ScanResult> {
+        log::info!(target: "rpc","executing rpc method 'scan_records'");
+        let endpoints = Config::get_config().query_endpoints(query.as_deref());
+        call_aleo_function!(scan::scan_records(
+            &view_key,
+            start_height,
+            end_height,
+            endpoints
+        ))
+        .to_jsonrpc_result()
+        .log_rpc_error("scan_records")
+    }
+
+    fn generate_account(&self) -> Result<Account> {
+        log::info!(target: "rpc","executing rpc method 'generate_account'");
+        call_aleo_function!(account::generate_account())
+            .to_jsonrpc_result()
+            .log_rpc_error("generate_account")
+    }
+
+    fn derive_account(&self, private_key: String) -> Result<Account> {
+        log::info!(target: "rpc","executing rpc method 'derive_account'");
+        call_aleo_function!(account::derive_account(&private_key))
+            .to_jsonrpc_result()
+            .log_rpc_error("derive_account")
+    }
+
+    fn sign(&self, private_key: String, message: String) -> Result<String> {
+        log::info!(target: "rpc","executing rpc method 'sign'");
+        call_aleo_function!(account::sign(&private_key, &message))
+            .to_jsonrpc_result()
+            .log_rpc_error("sign")
+    }
+
+    fn verify(&self, address: String, message: String, signature: String) -> Result<bool> {
+        log::info!(target: "rpc","executing rpc method 'verify'");
+        call_aleo_function!(account::verify(&address, &message, &signature))
+            .to_jsonrpc_result()
+            .log_rpc_error("verify")
+    }
+
     fn discovery(&self) -> Result<Discovery> {
         log::info!(target: "rpc","executing rpc method 'discovery'");
-        let client_secret = Config::get_config().get_secret_key().to_jsonrpc_result()?;
+        let config = Config::get_config();
+        let client_secret = config.get_secret_key().to_jsonrpc_result()?;
+        let network = match config.get_network_config() {
+            Ok(networks) => networks.active_network().map(|v| ActiveNetwork {
+                name: v.name.clone(),
+                kind: v.kind,
+                query: v.endpoints.first().cloned(),
+                path: v.path.clone(),
+            }),
+            Err(e) => {
+                log::error!(target: "rpc","cant read network config: {}", e);
+                None
+            }
+        };
         Ok(Discovery {
             version: env!("CARGO_PKG_VERSION").to_string(),
             features: vec![
@@ -336,14 +1130,38 @@ impl Rpc for RpcImpl {
                 "transfer".to_string(),
                 "join".to_string(),
                 "split".to_string(),
+                "deployv2".to_string(),
+                "executev2".to_string(),
+                "transferv2".to_string(),
+                "joinv2".to_string(),
+                "splitv2".to_string(),
                 "deployment_cost".to_string(),
                 "execution_costv2".to_string(),
+                "deployment_costv2".to_string(),
+                "execution_costv3".to_string(),
                 "decrypt_recordsv2".to_string(),
                 "transaction_from_authorization".to_string(),
                 "deploy_from_authorization".to_string(),
+                "authorize_execute".to_string(),
+                "authorize_fee".to_string(),
+                "authorize_deploy_fee".to_string(),
+                "broadcast".to_string(),
+                "execute_and_broadcast".to_string(),
+                "transaction_status".to_string(),
+                "verify_transaction".to_string(),
+                "execute_bundle".to_string(),
+                "scan_records".to_string(),
+                "generate_account".to_string(),
+                "derive_account".to_string(),
+                "sign".to_string(),
+                "verify".to_string(),
                 "update".to_string(),
+                "stats".to_string(),
+                "state_snapshot".to_string(),
             ],
+            transfer_functions: TransferFunction::supported(),
             pubkey: hex::encode(tls::get_p256_pubkey(&client_secret)),
+            network,
         })
     }
 
@@ -352,6 +1170,94 @@ impl Rpc for RpcImpl {
         update_dialog(&version);
         Ok(())
     }
+
+    fn stats(&self) -> Result<StatsReport> {
+        log::info!(target: "rpc","executing rpc method 'stats'");
+        Ok(stats::report())
+    }
+}
+
+/// no fee is a public fee without priority fee
+fn check_fee(fee: Option<Fee>) -> Result<Fee> {
+    let fee = fee.unwrap_or_default();
+    fee.validate().to_jsonrpc_result()?;
+    Ok(fee)
+}
+
+/// costs come back from aleowrap as a string of microcredits
+fn parse_cost(cost: String) -> anyhow::Result<u64> {
+    cost.trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("cant parse cost {}", cost))
+}
+
+fn check_no_fee(fee: Option<Fee>) -> Result<()> {
+    match fee {
+        Some(v) if !v.is_default() => Err(to_jsonrpc_error(anyhow::anyhow!(
+            "split doesn't take a fee"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// falls back to the active network when the caller doesn't pass a query endpoint,
+/// aleowrap reaches the endpoints through the query relay so the proxy config applies.
+/// with a state snapshot the request is proven from it alone and `query` is ignored,
+/// it fails right away when snarkvm's parameters would have to be downloaded
+fn resolve_query(
+    query: Option<String>,
+    snapshot: Option<StateSnapshot>,
+) -> anyhow::Result<Option<String>> {
+    match snapshot {
+        Some(snapshot) => {
+            parameters::check_local()?;
+            snapshot_url(snapshot).map(Some)
+        }
+        None => Ok(relay_url(
+            Config::get_config().query_endpoints(query.as_deref()),
+        )),
+    }
+}
+
+/// what the proving rpcs prove against, the same sources as `resolve_query`. programs
+/// come from the snapshot alone or from the program cache and the query endpoints
+fn resolve_state(
+    query: Option<String>,
+    snapshot: Option<StateSnapshot>,
+) -> anyhow::Result<ProvingState> {
+    match snapshot {
+        Some(snapshot) => {
+            parameters::check_local()?;
+            Ok(ProvingState {
+                programs: snapshot.programs.clone(),
+                query: snapshot_url(snapshot)?,
+                endpoints: Vec::new(),
+                offline: true,
+            })
+        }
+        None => {
+            let endpoints = Config::get_config().query_endpoints(query.as_deref());
+            let query = relay_url(endpoints.clone())
+                .ok_or_else(|| anyhow::anyhow!("no query endpoint configured"))?;
+            Ok(ProvingState {
+                query,
+                programs: HashMap::new(),
+                endpoints,
+                offline: false,
+            })
+        }
+    }
+}
+
+/// `imports` completed with the programs they and `program` import, from the program cache
+fn resolve_imports(
+    program: &str,
+    imports: Option<HashMap<String, String>>,
+    query: Option<&str>,
+) -> anyhow::Result<Option<HashMap<String, String>>> {
+    let endpoints = Config::get_config().query_endpoints(query);
+    let imports = programs::resolve_imports(program, imports.unwrap_or_default(), &endpoints)?;
+    Ok(Some(imports))
 }
 
 pub fn to_jsonrpc_error(err: anyhow::Error) -> jsonrpc_core::error::Error {
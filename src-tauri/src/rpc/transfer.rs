@@ -0,0 +1,147 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::aleo::is_credits_function;
+
+/// `function` of a transfer, which `credits.aleo` transfer function it calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferFunction {
+    Public,
+    Private,
+    PublicToPrivate,
+    PrivateToPublic,
+}
+
+impl TransferFunction {
+    pub const ALL: [Self; 4] = [
+        Self::Public,
+        Self::Private,
+        Self::PublicToPrivate,
+        Self::PrivateToPublic,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Private => "private",
+            Self::PublicToPrivate => "public_to_private",
+            Self::PrivateToPublic => "private_to_public",
+        }
+    }
+
+    pub fn credits_function(&self) -> String {
+        format!("transfer_{}", self.as_str())
+    }
+
+    /// whether the amount comes from `input_record` instead of the public balance
+    pub fn spends_record(&self) -> bool {
+        matches!(self, Self::Private | Self::PrivateToPublic)
+    }
+
+    /// the variants the network's `credits.aleo` has
+    pub fn supported() -> Vec<Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|v| is_credits_function(&v.credits_function()))
+            .collect()
+    }
+
+    /// checks what aleowrap would only find out after loading the keys
+    pub fn validate(&self, recipient: &str, amount: u64, input_record: Option<&str>) -> Result<()> {
+        if !is_credits_function(&self.credits_function()) {
+            return Err(anyhow!(
+                "credits.aleo of this network has no {}",
+                self.credits_function()
+            ));
+        }
+        if !recipient.starts_with("aleo1") {
+            return Err(anyhow!("recipient {} is not an address", recipient));
+        }
+        if amount == 0 {
+            return Err(anyhow!("amount must be above 0"));
+        }
+        match (self.spends_record(), input_record) {
+            (true, None) => Err(anyhow!(
+                "{} transfers spend a record, input_record is missing",
+                self
+            )),
+            (false, Some(_)) => Err(anyhow!(
+                "{} transfers are paid from the public balance, input_record must be left out",
+                self
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for TransferFunction {
+    type Err = anyhow::Error;
+
+    /// takes the variant or the name of the function, `public` or `transfer_public`
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.strip_prefix("transfer_").unwrap_or(s);
+        Self::ALL
+            .into_iter()
+            .find(|v| v.as_str() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|v| v.as_str()).collect();
+                anyhow!(
+                    "unknown transfer function {}, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for TransferFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RECIPIENT: &str = "aleo1rhgdu77hgyqd3xjj8ucu3jj9r2krwz6mnzyd80gncr5fxcwlh5rsvzp9px";
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "public_to_private".parse::<TransferFunction>().unwrap(),
+            TransferFunction::PublicToPrivate
+        );
+        assert_eq!(
+            "transfer_private".parse::<TransferFunction>().unwrap(),
+            TransferFunction::Private
+        );
+        let err = "privat".parse::<TransferFunction>().unwrap_err();
+        assert!(err.to_string().contains("expected one of public, private"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(TransferFunction::Public
+            .validate(RECIPIENT, 100, None)
+            .is_ok());
+        assert!(TransferFunction::Private
+            .validate(RECIPIENT, 100, None)
+            .is_err());
+        assert!(TransferFunction::PrivateToPublic
+            .validate(RECIPIENT, 100, Some("{ owner: aleo1.private }"))
+            .is_ok());
+        assert!(TransferFunction::PublicToPrivate
+            .validate(RECIPIENT, 100, Some("{ owner: aleo1.private }"))
+            .is_err());
+        assert!(TransferFunction::Public
+            .validate(RECIPIENT, 0, None)
+            .is_err());
+        assert!(TransferFunction::Public
+            .validate("APrivateKey1zkp", 100, None)
+            .is_err());
+    }
+}
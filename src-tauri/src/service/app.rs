@@ -9,7 +9,7 @@ use tauri::{api::dialog, Manager};
 use lazy_static::lazy_static;
 
 use crate::{
-    config::{consts, Config},
+    config::{profile::PROFILE, Config},
     tls,
 };
 
@@ -42,7 +42,7 @@ pub fn get_server_url() -> Result<String, String> {
         "http://{}@{}:{}",
         fingerprint,
         "127.0.0.1",
-        consts::RPC_PORT
+        PROFILE.rpc_port()
     );
 
     Ok(url)
@@ -0,0 +1,72 @@
+use std::{
+    cell::Cell,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Mutex,
+    },
+    thread,
+};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::app::APP_HANDLE;
+
+pub const EVENT_LOG_LINE: &str = "log-line";
+pub const EVENT_REQUEST_FINISHED: &str = "request-finished";
+
+// events waiting for the emitter thread, more than this and new ones are dropped
+const EVENT_QUEUE_SIZE: usize = 1024;
+
+struct Event {
+    name: &'static str,
+    payload: Value,
+}
+
+lazy_static! {
+    static ref EVENTS: Mutex<SyncSender<Event>> = Mutex::new(start_emitter());
+}
+
+thread_local! {
+    static EMITTER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Queues an event for all windows without waiting on the webview. Returns
+/// false when the queue is full, the frontend has to fetch what it missed.
+pub fn emit<S: Serialize>(name: &'static str, payload: &S) -> bool {
+    // logging from the emitter thread would feed its own queue
+    if EMITTER_THREAD.with(|v| v.get()) {
+        return false;
+    }
+    let payload = match serde_json::to_value(payload) {
+        Ok(v) => v,
+        Err(_) => return false,
+    };
+    match EVENTS.lock().unwrap().try_send(Event { name, payload }) {
+        Ok(_) => true,
+        Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => false,
+    }
+}
+
+fn start_emitter() -> SyncSender<Event> {
+    let (sender, receiver) = mpsc::sync_channel(EVENT_QUEUE_SIZE);
+    thread::Builder::new()
+        .name("event-emitter".to_string())
+        .spawn(move || run_emitter(receiver))
+        .expect("cant start event emitter");
+    sender
+}
+
+fn run_emitter(receiver: Receiver<Event>) {
+    EMITTER_THREAD.with(|v| v.set(true));
+    for event in receiver {
+        let handle = APP_HANDLE.lock().unwrap().clone();
+        // before the app is built there is no window to listen
+        if let Some(handle) = handle {
+            if let Err(e) = tauri::Manager::emit_all(&handle, event.name, event.payload) {
+                eprintln!("cant emit {}: {}", event.name, e);
+            }
+        }
+    }
+}
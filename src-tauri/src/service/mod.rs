@@ -1 +1,3 @@
 pub mod app;
+pub mod diagnostics;
+pub mod events;
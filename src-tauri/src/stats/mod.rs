@@ -0,0 +1,275 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, logger::redact::redact_error};
+
+const STATS_KEY: &str = "stats";
+const PREVIOUS_STATS_KEY: &str = "stats_previous";
+// percentiles are computed over this many of the latest calls of a method
+const DURATION_WINDOW: usize = 200;
+// recorded calls are saved at most this often, and on exit
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+static UNSAVED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref STATS: Mutex<Option<Stats>> = Mutex::new(None);
+    static ref SAVED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MethodStats {
+    count: u64,
+    success: u64,
+    max_ms: u64,
+    /// latest durations, oldest first
+    durations: VecDeque<u64>,
+    last_error: Option<String>,
+    last_error_at: Option<String>,
+}
+
+/// aggregates of every rpc request since `since`, by method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Stats {
+    since: String,
+    /// app version when the stats were started, on upgrade they move to `stats_previous`
+    version: String,
+    methods: BTreeMap<String, MethodStats>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            since: Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            methods: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodReport {
+    pub method: String,
+    pub count: u64,
+    pub success_rate: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub version: String,
+    pub since: String,
+    pub cores: usize,
+    pub methods: Vec<MethodReport>,
+    /// stats of the version used before this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Box<StatsReport>>,
+}
+
+impl MethodStats {
+    fn record(&mut self, duration_ms: u64, error: Option<String>) {
+        self.count += 1;
+        self.max_ms = self.max_ms.max(duration_ms);
+        self.durations.push_back(duration_ms);
+        if self.durations.len() > DURATION_WINDOW {
+            self.durations.pop_front();
+        }
+        match error {
+            Some(error) => {
+                self.last_error = Some(error);
+                self.last_error_at = Some(Local::now().to_rfc3339());
+            }
+            None => self.success += 1,
+        }
+    }
+
+    fn report(&self, method: &str) -> MethodReport {
+        let mut durations: Vec<u64> = self.durations.iter().copied().collect();
+        durations.sort_unstable();
+        MethodReport {
+            method: method.to_string(),
+            count: self.count,
+            success_rate: match self.count {
+                0 => 0.0,
+                count => self.success as f64 / count as f64,
+            },
+            p50_ms: percentile(&durations, 50),
+            p95_ms: percentile(&durations, 95),
+            max_ms: self.max_ms,
+            last_error: self.last_error.clone(),
+            last_error_at: self.last_error_at.clone(),
+        }
+    }
+}
+
+/// nearest rank percentile of sorted values
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percent * sorted.len() + 99) / 100;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Stats {
+    fn report(&self) -> StatsReport {
+        StatsReport {
+            version: self.version.clone(),
+            since: self.since.clone(),
+            cores: std::thread::available_parallelism()
+                .map(|v| v.get())
+                .unwrap_or(1),
+            methods: self
+                .methods
+                .iter()
+                .map(|(method, stats)| stats.report(method))
+                .collect(),
+            previous: None,
+        }
+    }
+}
+
+impl Config {
+    fn get_stats(&self) -> Result<Stats> {
+        let db = self.db.clone().context("cant get db")?;
+        let stats: Stats = match db.get(STATS_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode stats")?,
+            None => return Ok(Stats::default()),
+        };
+        // numbers from two versions mixed would hide a regression
+        if stats.version != env!("CARGO_PKG_VERSION") {
+            db.put(PREVIOUS_STATS_KEY, serde_json::to_vec(&stats)?)
+                .context("cant write to db")?;
+            return Ok(Stats::default());
+        }
+        Ok(stats)
+    }
+
+    fn get_previous_stats(&self) -> Result<Option<Stats>> {
+        let db = self.db.clone().context("cant get db")?;
+        match db.get(PREVIOUS_STATS_KEY).context("cant read db")? {
+            Some(v) => serde_json::from_slice(&v).context("cant decode stats"),
+            None => Ok(None),
+        }
+    }
+
+    fn set_stats(&self, stats: &Stats) -> Result<()> {
+        let db = self.db.clone().context("cant get db")?;
+        db.put(STATS_KEY, serde_json::to_vec(stats)?)
+            .context("cant write to db")
+    }
+}
+
+fn with_stats<R>(f: impl FnOnce(&mut Stats) -> R) -> R {
+    let mut stats = STATS.lock().unwrap();
+    let stats = stats.get_or_insert_with(|| {
+        Config::get_config().get_stats().unwrap_or_else(|e| {
+            log::warn!("cant read stats, starting over: {:#}", e);
+            Stats::default()
+        })
+    });
+    f(stats)
+}
+
+/// adds a finished rpc request to the stats of its method
+pub fn record(method: &str, duration: Duration, error: Option<&str>) {
+    let error = error.map(redact_error);
+    with_stats(|stats| {
+        stats
+            .methods
+            .entry(method.to_string())
+            .or_default()
+            .record(duration.as_millis() as u64, error)
+    });
+    UNSAVED.store(true, Ordering::SeqCst);
+
+    let mut saved_at = SAVED_AT.lock().unwrap();
+    if saved_at
+        .map(|v| v.elapsed() >= SAVE_INTERVAL)
+        .unwrap_or(true)
+    {
+        *saved_at = Some(Instant::now());
+        drop(saved_at);
+        flush();
+    }
+}
+
+/// saves the calls recorded since the last save
+pub fn flush() {
+    if !UNSAVED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = with_stats(|stats| Config::get_config().set_stats(stats)) {
+        log::error!("cant save stats: {:#}", e);
+    }
+}
+
+pub fn report() -> StatsReport {
+    let mut report = with_stats(|stats| stats.report());
+    match Config::get_config().get_previous_stats() {
+        Ok(previous) => report.previous = previous.map(|v| Box::new(v.report())),
+        Err(e) => log::warn!("cant read previous stats: {:#}", e),
+    }
+    report
+}
+
+#[tauri::command]
+pub fn get_stats() -> StatsReport {
+    report()
+}
+
+#[tauri::command]
+pub fn reset_stats() -> Result<(), String> {
+    with_stats(|stats| {
+        *stats = Stats::default();
+        Config::get_config().set_stats(stats)
+    })
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50), 50);
+        assert_eq!(percentile(&sorted, 95), 95);
+        assert_eq!(percentile(&[7], 95), 7);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn test_method_report() {
+        let mut stats = MethodStats::default();
+        for i in 1..=DURATION_WINDOW as u64 + 10 {
+            stats.record(i, None);
+        }
+        stats.record(5, Some("no fee record".to_string()));
+
+        let report = stats.report("transfer");
+        assert_eq!(report.count, DURATION_WINDOW as u64 + 11);
+        assert_eq!(report.max_ms, DURATION_WINDOW as u64 + 10);
+        assert!(report.success_rate < 1.0 && report.success_rate > 0.99);
+        assert_eq!(report.last_error.as_deref(), Some("no fee record"));
+        // the oldest calls left the window
+        assert_eq!(stats.durations.len(), DURATION_WINDOW);
+        assert!(report.p50_ms > 100);
+    }
+}
@@ -0,0 +1,216 @@
+pub mod otlp;
+
+use std::{
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use lazy_static::lazy_static;
+use rand_core::{OsRng, RngCore};
+use serde_json::Value;
+
+use crate::{
+    config::{telemetry::TelemetryConfig, Config},
+    rpc::context,
+};
+
+pub const HEADER_TRACEPARENT: &str = "traceparent";
+
+lazy_static! {
+    static ref TELEMETRY: RwLock<TelemetryConfig> = RwLock::new(TelemetryConfig::default());
+}
+
+/// ids of a span, what `traceparent` carries between services
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+}
+
+impl SpanContext {
+    /// parses a w3c `traceparent` header, `00-<trace id>-<parent id>-<flags>`
+    pub fn from_traceparent(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = hex::decode(parts.next()?).ok()?;
+        let span_id = hex::decode(parts.next()?).ok()?;
+        parts.next()?;
+        if version.len() != 2 || version == "ff" {
+            return None;
+        }
+        let context = Self {
+            trace_id: trace_id.try_into().ok()?,
+            span_id: span_id.try_into().ok()?,
+        };
+        // all zero ids are invalid
+        if context.trace_id == [0; 16] || context.span_id == [0; 8] {
+            return None;
+        }
+        Some(context)
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-01",
+            hex::encode(self.trace_id),
+            hex::encode(self.span_id)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Internal = 1,
+    Server = 2,
+    Client = 3,
+}
+
+/// A unit of work sent to the collector when it ends. Spans are only recorded
+/// while export is enabled, otherwise every method is a no-op.
+#[derive(Debug)]
+pub struct Span {
+    recording: bool,
+    pub(crate) context: SpanContext,
+    pub(crate) parent_span_id: Option<[u8; 8]>,
+    pub(crate) name: String,
+    pub(crate) kind: SpanKind,
+    pub(crate) start: SystemTime,
+    pub(crate) end: SystemTime,
+    pub(crate) attributes: Vec<(String, Value)>,
+    pub(crate) error: Option<String>,
+}
+
+impl Span {
+    /// starts a span under `parent`, or a new trace without one
+    pub fn start(name: &str, kind: SpanKind, parent: Option<SpanContext>) -> Self {
+        let recording = is_enabled();
+        let mut span_id = [0u8; 8];
+        let mut trace_id = [0u8; 16];
+        if recording {
+            OsRng.fill_bytes(&mut span_id);
+            match parent {
+                Some(v) => trace_id = v.trace_id,
+                None => OsRng.fill_bytes(&mut trace_id),
+            }
+        }
+        let now = SystemTime::now();
+        Self {
+            recording,
+            context: SpanContext { trace_id, span_id },
+            parent_span_id: parent.map(|v| v.span_id),
+            name: name.to_string(),
+            kind,
+            start: now,
+            end: now,
+            attributes: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// starts a span under the span of the rpc request being handled
+    pub fn child(name: &str, kind: SpanKind) -> Self {
+        let parent = context::current().and_then(|v| v.trace);
+        Self::start(name, kind, parent)
+    }
+
+    /// context to hand to children, none when the span isn't recorded
+    pub fn context(&self) -> Option<SpanContext> {
+        self.recording.then_some(self.context)
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: impl Into<Value>) {
+        if self.recording {
+            self.attributes.push((key.to_string(), value.into()));
+        }
+    }
+
+    pub fn set_error(&mut self, error: impl ToString) {
+        if self.recording {
+            self.error = Some(error.to_string());
+        }
+    }
+
+    pub fn end(mut self) {
+        if self.recording {
+            self.end = SystemTime::now();
+            otlp::queue(self);
+        }
+    }
+}
+
+pub(crate) fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|v| v.as_nanos())
+        .unwrap_or_default()
+}
+
+pub fn is_enabled() -> bool {
+    TELEMETRY.read().unwrap().enabled
+}
+
+pub(crate) fn current_config() -> TelemetryConfig {
+    TELEMETRY.read().unwrap().clone()
+}
+
+pub fn setup_telemetry() {
+    match Config::get_config().get_telemetry_config() {
+        Ok(v) => apply_config(v),
+        Err(e) => log::warn!("cant read telemetry config: {:#}", e),
+    }
+}
+
+fn apply_config(telemetry: TelemetryConfig) {
+    if telemetry.enabled {
+        log::info!("exporting spans to {}", telemetry.endpoint);
+    }
+    *TELEMETRY.write().unwrap() = telemetry;
+}
+
+#[tauri::command]
+pub fn get_telemetry_config() -> Result<TelemetryConfig, String> {
+    Config::get_config()
+        .get_telemetry_config()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_telemetry_config(telemetry: TelemetryConfig) -> Result<(), String> {
+    Config::get_config()
+        .set_telemetry_config(&telemetry)
+        .map_err(|e| e.to_string())?;
+    apply_config(telemetry);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_traceparent() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = SpanContext::from_traceparent(header).unwrap();
+        assert_eq!(
+            hex::encode(context.trace_id),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(hex::encode(context.span_id), "00f067aa0ba902b7");
+        assert_eq!(context.traceparent(), header);
+
+        assert!(SpanContext::from_traceparent("00-4bf92f3577b34da6-00f067aa0ba902b7-01").is_none());
+        assert!(SpanContext::from_traceparent(
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01"
+        )
+        .is_none());
+        assert!(SpanContext::from_traceparent("garbage").is_none());
+    }
+
+    #[test]
+    fn test_disabled_span_is_noop() {
+        let mut span = Span::start("rpc", SpanKind::Server, None);
+        span.set_attribute("rpc.method", "transfer");
+        assert!(span.context().is_none());
+        assert!(span.attributes.is_empty());
+        span.end();
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+
+use crate::{config::telemetry::TelemetryConfig, net::HttpClient};
+
+use super::{current_config, unix_nanos, Span};
+
+// ended spans waiting for export, more than this and new ones are dropped
+const SPAN_QUEUE_SIZE: usize = 2048;
+const MAX_BATCH: usize = 256;
+const BATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    static ref SPANS: Mutex<SyncSender<Span>> = Mutex::new(start_exporter());
+}
+
+pub(super) fn queue(span: Span) {
+    // a full queue means the collector is slow or gone, tracing must not block requests
+    let _ = SPANS.lock().unwrap().try_send(span);
+}
+
+fn start_exporter() -> SyncSender<Span> {
+    let (sender, receiver) = mpsc::sync_channel(SPAN_QUEUE_SIZE);
+    thread::Builder::new()
+        .name("otlp-exporter".to_string())
+        .spawn(move || run_exporter(receiver))
+        .expect("cant start otlp exporter");
+    sender
+}
+
+fn run_exporter(receiver: Receiver<Span>) {
+    let mut batch = Vec::new();
+    loop {
+        let closed = match receiver.recv_timeout(BATCH_INTERVAL) {
+            Ok(span) => {
+                batch.push(span);
+                if batch.len() < MAX_BATCH {
+                    continue;
+                }
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        if !batch.is_empty() {
+            let config = current_config();
+            if let Err(e) = export(&config, &batch) {
+                // not logged, the log line would be traced again when rpc spans log
+                eprintln!("cant export {} spans: {:#}", batch.len(), e);
+            }
+            batch.clear();
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+/// posts spans to the collector as an otlp/http json request
+pub fn export(config: &TelemetryConfig, spans: &[Span]) -> Result<()> {
+    let body = serde_json::to_vec(&encode(config, spans))?;
+    // the collector is reached directly, it usually runs next to the service
+    let response = HttpClient::with_proxy(None).request("POST", &config.endpoint, Some(&body))?;
+    if !response.is_success() {
+        return Err(anyhow!(
+            "collector responded with status {}",
+            response.status
+        ));
+    }
+    Ok(())
+}
+
+fn encode(config: &TelemetryConfig, spans: &[Span]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute("service.name", &Value::from(config.service_name.as_str()))],
+            },
+            "scopeSpans": [{
+                "scope": {
+                    "name": config.service_name,
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "spans": spans.iter().map(encode_span).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+fn encode_span(span: &Span) -> Value {
+    let mut value = json!({
+        "traceId": hex::encode(span.context.trace_id),
+        "spanId": hex::encode(span.context.span_id),
+        "name": span.name,
+        "kind": span.kind as u8,
+        // 64 bit numbers are strings in otlp json
+        "startTimeUnixNano": unix_nanos(span.start).to_string(),
+        "endTimeUnixNano": unix_nanos(span.end).to_string(),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| attribute(key, value))
+            .collect::<Vec<_>>(),
+        "status": match &span.error {
+            Some(message) => json!({ "code": 2, "message": message }),
+            None => json!({ "code": 1 }),
+        },
+    });
+    if let Some(parent) = span.parent_span_id {
+        value["parentSpanId"] = Value::from(hex::encode(parent));
+    }
+    value
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(v) => json!({ "boolValue": v }),
+        Value::Number(v) if v.is_i64() || v.is_u64() => json!({ "intValue": v.to_string() }),
+        Value::Number(v) => json!({ "doubleValue": v }),
+        Value::String(v) => json!({ "stringValue": v }),
+        v => json!({ "stringValue": v.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        time::SystemTime,
+    };
+
+    use super::{super::SpanContext, *};
+    use crate::telemetry::SpanKind;
+
+    /// accepts one request like an otlp collector would and hands back its body
+    fn collector() -> (String, thread::JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/traces", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .unwrap();
+            (request_line, body)
+        });
+        (url, handle)
+    }
+
+    fn span(parent: Option<SpanContext>) -> Span {
+        let now = SystemTime::now();
+        Span {
+            recording: true,
+            context: SpanContext {
+                trace_id: [1; 16],
+                span_id: [2; 8],
+            },
+            parent_span_id: parent.map(|v| v.span_id),
+            name: "aleowrap transfer".to_string(),
+            kind: SpanKind::Internal,
+            start: now,
+            end: now + Duration::from_millis(1520),
+            attributes: vec![("rpc.method".to_string(), Value::from("transfer"))],
+            error: Some("no fee record".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_export_to_collector() {
+        let (url, handle) = collector();
+        let config = TelemetryConfig {
+            enabled: true,
+            endpoint: url,
+            ..Default::default()
+        };
+        let parent = SpanContext::from_traceparent(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        );
+        export(&config, &[span(parent)]).unwrap();
+
+        let (request_line, body) = handle.join().unwrap();
+        assert!(request_line.starts_with("POST /v1/traces"));
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let resource = &body["resourceSpans"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "aleo-acc-service"
+        );
+        let exported = &resource["scopeSpans"][0]["spans"][0];
+        assert_eq!(exported["name"], "aleowrap transfer");
+        assert_eq!(exported["traceId"], "01010101010101010101010101010101");
+        assert_eq!(exported["parentSpanId"], "00f067aa0ba902b7");
+        assert_eq!(exported["status"]["code"], 2);
+        assert_eq!(exported["attributes"][0]["key"], "rpc.method");
+        let start: u128 = exported["startTimeUnixNano"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let end: u128 = exported["endTimeUnixNano"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(end - start, 1_520_000_000);
+    }
+}
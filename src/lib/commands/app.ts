@@ -19,4 +19,8 @@ export interface BuildInfo {
 
 export async function get_build_info(): Promise<BuildInfo> {
     return await invoke('get_build_info', {})
-}
\ No newline at end of file
+}
+
+export async function export_diagnostics(path: string) {
+    await invoke('export_diagnostics', { path: path })
+}
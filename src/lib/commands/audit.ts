@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface AuditRecord {
+    id: string,
+    timestamp: string,
+    client?: string,
+    method?: string,
+    params: { [name: string]: any },
+    duration_ms: number,
+    success: boolean,
+    error?: string,
+    transaction_id?: string,
+    /** transactions of an execute_bundle call, in step order */
+    transaction_ids: string[],
+}
+
+export interface AuditQuery {
+    method?: string,
+    client?: string,
+    from?: string,
+    to?: string,
+    success?: boolean,
+    limit?: number,
+}
+
+export interface AuditRetention {
+    max_records: number,
+    max_age_days: number,
+}
+
+export async function get_audit_records(query?: AuditQuery): Promise<AuditRecord[]> {
+    return await invoke('get_audit_records', { query: query })
+}
+
+export async function clear_audit_records() {
+    await invoke('clear_audit_records', {})
+}
+
+export async function get_audit_retention(): Promise<AuditRetention> {
+    return await invoke('get_audit_retention', {})
+}
+
+export async function set_audit_retention(retention: AuditRetention) {
+    await invoke('set_audit_retention', { retention: retention })
+}
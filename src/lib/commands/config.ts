@@ -1,13 +1,29 @@
 import { invoke } from "@tauri-apps/api/tauri";
 
-export async function set_proxy(proxy: String) {
+export type ProxyScheme = 'http' | 'https' | 'socks5' | 'socks5h';
+
+export interface ProxyConfig {
+    scheme: ProxyScheme,
+    host: string,
+    port: number,
+    username?: string,
+    // only sent to the service, leave it out to keep the saved password
+    password?: string,
+    bypass: string[],
+}
+
+export async function set_proxy(proxy: ProxyConfig | null) {
     return await invoke('set_proxy', { proxy: proxy })
 }
 
-export async function get_proxy(): Promise<string | null> {
+export async function get_proxy(): Promise<ProxyConfig | null> {
     return await invoke('get_proxy', {})
 }
 
-export async function test_proxy(proxy: String): Promise<string | null> {
+export async function test_proxy(proxy: ProxyConfig): Promise<string | null> {
     return await invoke('test_proxy', { proxy: proxy })
-}
\ No newline at end of file
+}
+
+export function proxy_url(proxy: ProxyConfig | null): string {
+    return proxy ? `${proxy.scheme}://${proxy.host}:${proxy.port}` : ""
+}
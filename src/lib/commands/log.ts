@@ -1,9 +1,93 @@
 import { invoke } from "@tauri-apps/api/tauri";
 
-export async function get_logs(): Promise<string[]> {
-    return await invoke('get_logs', {})
+export interface LogRecord {
+    seq: number,
+    timestamp: string,
+    level: string,
+    target: string,
+    message: string,
+    request_id?: string,
+    client?: string,
+    method?: string,
+    duration_ms?: number,
+}
+
+export interface LogQuery {
+    level?: string,
+    target?: string,
+    method?: string,
+    request_id?: string,
+    search?: string,
+    regex?: boolean,
+    from?: string,
+    to?: string,
+    limit?: number,
+}
+
+export async function get_logs(query?: LogQuery): Promise<LogRecord[]> {
+    return await invoke('get_logs', { query: query })
+}
+
+export function log_record_text(record: LogRecord): string {
+    const tags = [record.request_id, record.method, record.duration_ms !== undefined ? `${record.duration_ms}ms` : undefined]
+        .filter((v) => v !== undefined);
+    const time = record.timestamp.slice(0, 19).replace('T', ' ');
+    const tag = tags.length ? ` [${tags.join(' ')}]` : '';
+    return `${time} :: ${record.level} ${record.target}${tag} - ${record.message}`
 }
 
 export async function clear_logs() {
     await invoke('clear_logs', {})
 }
+
+export interface LogLine {
+    seq: number,
+    line: string,
+}
+
+export interface LogsSince {
+    lines: LogLine[],
+    next_seq: number,
+    missed: boolean,
+}
+
+export async function get_logs_since(seq: number): Promise<LogsSince> {
+    return await invoke('get_logs_since', { seq: seq })
+}
+
+export interface LogRetention {
+    max_file_size: number,
+    max_files: number,
+    max_age_days: number,
+}
+
+export async function get_log_retention(): Promise<LogRetention> {
+    return await invoke('get_log_retention', {})
+}
+
+export async function set_log_retention(retention: LogRetention) {
+    await invoke('set_log_retention', { retention: retention })
+}
+
+export type LogFormat = 'text' | 'json';
+
+export async function get_log_format(): Promise<LogFormat> {
+    return await invoke('get_log_format', {})
+}
+
+export async function set_log_format(format: LogFormat) {
+    await invoke('set_log_format', { format: format })
+}
+
+export interface LogConfig {
+    level: string,
+    targets: { [target: string]: string },
+}
+
+export async function get_log_config(): Promise<LogConfig> {
+    return await invoke('get_log_config', {})
+}
+
+export async function set_log_config(logConfig: LogConfig) {
+    await invoke('set_log_config', { logConfig: logConfig })
+}
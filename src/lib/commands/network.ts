@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export type NetworkKind = 'mainnet' | 'testnet' | 'devnet' | 'custom';
+
+export interface NetworkProfile {
+    name: string,
+    kind: NetworkKind,
+    endpoints: string[],
+    path: string,
+}
+
+export interface NetworkConfig {
+    active: string,
+    networks: NetworkProfile[],
+}
+
+export async function get_networks(): Promise<NetworkConfig> {
+    return await invoke('get_networks', {})
+}
+
+export async function set_active_network(name: string) {
+    await invoke('set_active_network', { name: name })
+}
+
+export async function save_network(network: NetworkProfile) {
+    await invoke('save_network', { network: network })
+}
+
+export async function remove_network(name: string) {
+    await invoke('remove_network', { name: name })
+}
@@ -0,0 +1,20 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface PrewarmConfig {
+    on_startup: boolean,
+    /** `<program id>/<function>` */
+    functions: string[],
+}
+
+export async function get_prewarm_config(): Promise<PrewarmConfig> {
+    return await invoke('get_prewarm_config', {})
+}
+
+export async function set_prewarm_config(prewarm: PrewarmConfig) {
+    await invoke('set_prewarm_config', { prewarm: prewarm })
+}
+
+/** computes the keys of `functions`, or the configured ones, in the background */
+export async function prewarm(functions?: string[]) {
+    await invoke('prewarm', { functions: functions })
+}
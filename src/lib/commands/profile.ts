@@ -0,0 +1,16 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface ProfileInfo {
+    name: string,
+    data_dir: string,
+    port: number,
+    profiles: string[],
+}
+
+export async function get_profile(): Promise<ProfileInfo> {
+    return await invoke('get_profile', {})
+}
+
+export async function switch_profile(name: string) {
+    await invoke('switch_profile', { name: name })
+}
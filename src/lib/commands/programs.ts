@@ -0,0 +1,18 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface ProgramEntry {
+    program_id: string,
+    hash: string,
+    edition?: number,
+    size: number,
+    fetched_at: string,
+}
+
+export async function list_program_cache(): Promise<ProgramEntry[]> {
+    return await invoke('list_program_cache', {})
+}
+
+/** removes `program_id`, or every cached program, and returns how many were removed */
+export async function purge_program_cache(program_id?: string): Promise<number> {
+    return await invoke('purge_program_cache', { programId: program_id })
+}
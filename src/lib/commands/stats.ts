@@ -0,0 +1,28 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface MethodReport {
+    method: string,
+    count: number,
+    success_rate: number,
+    p50_ms: number,
+    p95_ms: number,
+    max_ms: number,
+    last_error?: string,
+    last_error_at?: string,
+}
+
+export interface StatsReport {
+    version: string,
+    since: string,
+    cores: number,
+    methods: MethodReport[],
+    previous?: StatsReport,
+}
+
+export async function get_stats(): Promise<StatsReport> {
+    return await invoke('get_stats', {})
+}
+
+export async function reset_stats() {
+    await invoke('reset_stats', {})
+}
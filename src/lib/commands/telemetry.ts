@@ -0,0 +1,15 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface TelemetryConfig {
+    enabled: boolean,
+    endpoint: string,
+    service_name: string,
+}
+
+export async function get_telemetry_config(): Promise<TelemetryConfig> {
+    return await invoke('get_telemetry_config', {})
+}
+
+export async function set_telemetry_config(telemetry: TelemetryConfig) {
+    await invoke('set_telemetry_config', { telemetry: telemetry })
+}